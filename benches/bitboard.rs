@@ -0,0 +1,61 @@
+//! Compare win detection and move generation on the `Field` board with the bitboard.
+//!
+//! Run with `cargo bench`.
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tic_tac_toe::tic_tac_toe::{Dimensions, Field};
+use tic_tac_toe::tic_tac_toe::bitboard::BitBoard;
+use tic_tac_toe::tic_tac_toe::check_functions::{cats_game, someone_has_won};
+
+/// A handful of positions in different stages of the game, including won and drawn ones.
+fn positions() -> Vec<Vec<Field>> {
+    vec![
+        vec![Field::Free; 9],
+        vec![Field::Cross, Field::Free, Field::Free, Field::Free, Field::Circle, Field::Free, Field::Free, Field::Free, Field::Cross],
+        vec![Field::Circle, Field::Cross, Field::Cross, Field::Free, Field::Circle, Field::Free, Field::Cross, Field::Free, Field::Circle],
+        vec![Field::Circle, Field::Circle, Field::Cross, Field::Cross, Field::Cross, Field::Circle, Field::Circle, Field::Cross, Field::Cross],
+    ]
+}
+
+fn win_detection(c: &mut Criterion) {
+    let boards = positions();
+    let bitboards: Vec<BitBoard> = boards.iter().map(|board| BitBoard::from_fields(board)).collect();
+    let dims = Dimensions::default();
+    let mut group = c.benchmark_group("win detection");
+    group.bench_function("fields", |b| b.iter(|| {
+        for board in &boards {
+            black_box(someone_has_won(black_box(board), &dims));
+        }
+    }));
+    group.bench_function("bitboard", |b| b.iter(|| {
+        for bitboard in &bitboards {
+            black_box(black_box(bitboard).winner());
+        }
+    }));
+    group.finish();
+}
+
+fn move_generation(c: &mut Criterion) {
+    let boards = positions();
+    let bitboards: Vec<BitBoard> = boards.iter().map(|board| BitBoard::from_fields(board)).collect();
+    let mut group = c.benchmark_group("move generation");
+    group.bench_function("fields", |b| b.iter(|| {
+        for board in &boards {
+            if !cats_game(black_box(board)) {
+                let moves: Vec<usize> = (0..9).filter(|i| board[*i] == Field::Free).collect();
+                black_box(moves);
+            }
+        }
+    }));
+    group.bench_function("bitboard", |b| b.iter(|| {
+        for bitboard in &bitboards {
+            if !black_box(bitboard).is_full() {
+                let moves: Vec<usize> = bitboard.available_moves().collect();
+                black_box(moves);
+            }
+        }
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, win_detection, move_generation);
+criterion_main!(benches);
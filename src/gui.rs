@@ -1,42 +1,337 @@
 use fltk::{
+    app,
+    browser::HoldBrowser,
     button::Button,
-    enums::{Color, Event, FrameType},
+    draw,
+    enums::{Align, Color, Event, FrameType, Key},
+    frame::Frame,
+    group::Group,
+    menu::Choice,
     prelude::*,
+    valuator::HorNiceSlider,
 };
+use std::cell::{Cell, RefCell};
 use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+use std::sync::OnceLock;
+use crate::tic_tac_toe::{Field, Message, Number};
 
+mod theme;
+pub use theme::*;
+mod variant;
+pub use variant::*;
+mod ultimate;
+pub use ultimate::*;
+mod qubic;
+pub use qubic::*;
+mod gomoku;
+pub use gomoku::*;
+mod morris;
+pub use morris::*;
+mod wild;
+pub use wild::*;
+mod notakto;
+pub use notakto::*;
+mod numerical;
+pub use numerical::*;
 
+/// Environment variable to zoom the GUI, e.g. `TIC_TAC_TOE_ZOOM=1.5`.
+pub const ZOOM_VARIABLE: &str = "TIC_TAC_TOE_ZOOM";
 
+/// Scale factors FLTK detected for every screen before any zoom was applied.
+static SYSTEM_SCALES: OnceLock<Vec<f32>> = OnceLock::new();
+
+/// Read the zoom factor from `ZOOM_VARIABLE`, falling back to 1.0 for missing or invalid values.
+#[must_use]
+pub fn zoom_from_env() -> f32 {
+    std::env::var(ZOOM_VARIABLE)
+        .ok()
+        .and_then(|value| value.trim().parse::<f32>().ok())
+        .filter(|zoom| (0.25..=4.0).contains(zoom))
+        .unwrap_or(1.0)
+}
+
+/// Scale all widgets and labels by `zoom` on top of the DPI scaling of each screen.
+pub fn set_zoom(zoom: f32) {
+    let system_scales = SYSTEM_SCALES.get_or_init(|| (0..app::screen_count()).map(app::screen_scale).collect());
+    for (screen, scale) in system_scales.iter().enumerate() {
+        app::set_screen_scale(screen as i32, scale * zoom);
+    }
+}
+
+
+/// Number of frames used to animate a newly placed mark.
+const ANIMATION_STEPS: i32 = 8;
+/// Time between two frames of an animation in seconds.
+const ANIMATION_FRAME_TIME: f64 = 0.02;
+/// Time a dropped mark takes to fall by one row in seconds.
+const DROP_FRAME_TIME: f64 = 0.05;
+
+/// Colours of the heat map for lost, drawn and won moves.
+const HEAT_COLORS: [u32; 3] = [0xd3_2f2f, 0xfb_c02d, 0x38_8e3c];
+
+/// Value of a free cell shown on top of it: a number from -1 (lost) to 1 (won) that sets the
+/// colour, and a short caption.
+pub type Overlay = (f64, String);
+
+/// What a single cell currently shows.
+#[derive(Clone, Debug)]
+struct CellState {
+    mark: Field,
+    /// Mark previewed when the mouse is over the free cell.
+    ghost: Field,
+    hovered: bool,
+    highlighted: bool,
+    /// The mark is drawn faded because it vanishes soon.
+    fading: bool,
+    /// The cell is greyed out because it cannot be played any more.
+    dimmed: bool,
+    /// Number shown instead of a mark in Numerical tic-tac-toe.
+    number: Number,
+    /// Number previewed when the mouse is over the free cell.
+    ghost_number: Number,
+    /// Size of the mark relative to its full size, used to animate placed marks.
+    scale: f64,
+    overlay: Option<Overlay>,
+}
+
+#[derive(Clone)]
 pub struct MyButton {
     btn: Button,
+    state: Rc<RefCell<CellState>>,
+    theme: Rc<RefCell<Theme>>,
 }
 
 impl MyButton {
     #[must_use]
     pub fn new() -> MyButton {
+        MyButton::with_theme(Rc::new(RefCell::new(Theme::default())))
+    }
+
+    /// Create a button whose colours follow a theme shared with other buttons.
+    #[must_use]
+    pub fn with_theme(theme: Rc<RefCell<Theme>>) -> MyButton {
         let mut b = MyButton {
-            btn: Button::new(0, 0, 200, 0, ""),
+            btn: Button::new(0, 0, 0, 0, ""),
+            state: Rc::new(RefCell::new(CellState {
+                mark: Field::Free,
+                ghost: Field::Free,
+                hovered: false,
+                highlighted: false,
+                fading: false,
+                dimmed: false,
+                number: None,
+                ghost_number: None,
+                scale: 1.0,
+                overlay: None,
+            })),
+            theme,
         };
-        b.set_label_size(100);
         b.set_frame(FrameType::GleamUpBox);
-        b.set_label_color(Color::White);
-        b.set_selection_color(Color::from_hex(0x001b_1b1b));
-        b.set_color(Color::from_hex(0x42_4242));
+        b.apply_theme();
+        let state = b.state.clone();
+        let theme = b.theme.clone();
         b.handle(move |b, ev| match ev {
-            Event::Enter => {
-                b.set_color(Color::from_hex(0x2b_2b2b));
-                b.redraw();
-                true
-            }
-            Event::Leave => {
-                b.set_color(Color::from_hex(0x42_4242));
+            Event::Enter | Event::Leave => {
+                state.borrow_mut().hovered = ev == Event::Enter;
+                b.set_color(cell_color(&state.borrow(), &theme.borrow()));
                 b.redraw();
                 true
             }
             _ => false,
         });
+        let state = b.state.clone();
+        let theme = b.theme.clone();
+        b.draw(move |b| draw_piece(b, &state.borrow(), &theme.borrow()));
         b
     }
+
+    /// Update the colours after the shared theme was changed.
+    pub fn apply_theme(&mut self) {
+        let theme = self.theme.borrow().clone();
+        self.btn.set_label_color(Color::from_hex(theme.text));
+        self.btn.set_selection_color(Color::from_hex(theme.cell_pressed));
+        self.btn.set_color(cell_color(&self.state.borrow(), &theme));
+        self.btn.redraw();
+    }
+
+    /// Mark the button as part of the winning line (or remove the mark again).
+    pub fn set_highlight(&mut self, highlight: bool) {
+        self.state.borrow_mut().highlighted = highlight;
+        self.apply_theme();
+    }
+
+    /// Return the mark shown on the button.
+    #[must_use]
+    pub fn mark(&self) -> Field {
+        self.state.borrow().mark
+    }
+
+    /// Show a mark; the change becomes visible with the next redraw.
+    pub fn set_mark(&mut self, mark: Field) {
+        let mut state = self.state.borrow_mut();
+        state.mark = mark;
+        state.scale = 1.0;
+    }
+
+    /// Show the value of the free cell as a coloured overlay, `None` to remove it.
+    pub fn set_overlay(&mut self, overlay: Option<Overlay>) {
+        self.state.borrow_mut().overlay = overlay;
+        self.btn.redraw();
+    }
+
+    /// Set the mark that is previewed while the mouse is over the free button.
+    pub fn set_ghost(&mut self, ghost: Field) {
+        self.state.borrow_mut().ghost = ghost;
+    }
+
+    /// Show a number instead of a mark; the change becomes visible with the next redraw.
+    pub fn set_number(&mut self, number: Number) {
+        let mut state = self.state.borrow_mut();
+        state.number = number;
+        state.scale = 1.0;
+    }
+
+    /// Set the number that is previewed while the mouse is over the free button.
+    pub fn set_ghost_number(&mut self, number: Number) {
+        self.state.borrow_mut().ghost_number = number;
+    }
+
+    /// Draw the mark faded because it vanishes with the next move of its owner.
+    pub fn set_fading(&mut self, fading: bool) {
+        self.state.borrow_mut().fading = fading;
+        self.btn.redraw();
+    }
+
+    /// Grey out the cell and its mark because it cannot be played any more.
+    pub fn set_dimmed(&mut self, dimmed: bool) {
+        self.state.borrow_mut().dimmed = dimmed;
+        self.apply_theme();
+    }
+
+    /// Let a newly placed mark grow to its full size.
+    pub fn animate_mark(&mut self) {
+        let mut but = self.clone();
+        let mut step = 1;
+        but.state.borrow_mut().scale = 1.0 / f64::from(ANIMATION_STEPS);
+        but.redraw();
+        app::add_timeout3(ANIMATION_FRAME_TIME, move |handle| {
+            step += 1;
+            but.state.borrow_mut().scale = f64::from(step) / f64::from(ANIMATION_STEPS);
+            but.redraw();
+            if step < ANIMATION_STEPS {
+                app::repeat_timeout3(ANIMATION_FRAME_TIME, handle);
+            }
+        });
+    }
+}
+
+/// Background colour of a cell in its current state.
+fn cell_color(state: &CellState, theme: &Theme) -> Color {
+    if state.highlighted {
+        Color::from_hex(theme.highlight)
+    }
+    else if state.dimmed {
+        Color::color_average(Color::from_hex(theme.cell), Color::from_hex(theme.window), 0.4)
+    }
+    else if state.hovered {
+        Color::from_hex(theme.cell_hover)
+    }
+    else {
+        Color::from_hex(theme.cell)
+    }
+}
+
+/// Colour of the heat map for a value from -1 (lost) to 1 (won).
+fn heat_color(value: f64) -> Color {
+    let value = value.clamp(-1.0, 1.0);
+    let (from, to, weight) = if value < 0.0 { (HEAT_COLORS[0], HEAT_COLORS[1], value + 1.0) } else { (HEAT_COLORS[1], HEAT_COLORS[2], value) };
+    Color::color_average(Color::from_hex(to), Color::from_hex(from), weight as f32)
+}
+
+/// Fill a free cell with the colour of its value and write the caption in its center.
+fn draw_overlay(b: &Button, overlay: &Overlay) {
+    let inset = (b.w().min(b.h()) / 10).max(1);
+    draw::set_draw_color(Color::color_average(heat_color(overlay.0), b.color(), 0.6));
+    draw::draw_rectf(b.x() + inset, b.y() + inset, b.w() - 2 * inset, b.h() - 2 * inset);
+    draw::set_draw_color(b.label_color());
+    draw::set_font(draw::font(), (b.h() / 5).max(8));
+    draw::draw_text2(&overlay.1, b.x(), b.y(), b.w(), b.h(), Align::Center);
+}
+
+/// Colour of a player's pieces.
+fn piece_color(mark: Field, theme: &Theme) -> Color {
+    match mark {
+        Field::Cross => Color::from_hex(theme.cross),
+        Field::Circle => Color::from_hex(theme.circle),
+        Field::Free => Color::from_hex(theme.cell),
+    }
+}
+
+/// Colour of a number: odd numbers have the colour of the crosses, even ones that of the
+/// circles.
+fn number_color(number: u8, theme: &Theme) -> Color {
+    piece_color(if number % 2 == 1 { Field::Cross } else { Field::Circle }, theme)
+}
+
+/// Draw the number (or the preview of a number) of a cell in its center.
+fn draw_number(b: &Button, state: &CellState, theme: &Theme) -> bool {
+    let (number, color, scale) = match (state.number, state.ghost_number) {
+        (Some(number), _) => (number, number_color(number, theme), state.scale),
+        (None, Some(number)) if state.hovered => (number, Color::color_average(number_color(number, theme), b.color(), 0.35), 1.0),
+        _ => return false,
+    };
+    draw::set_draw_color(color);
+    draw::set_font(draw::font(), (f64::from(b.h()) * 0.6 * scale).max(8.0) as i32);
+    draw::draw_text2(&number.to_string(), b.x(), b.y(), b.w(), b.h(), Align::Center);
+    true
+}
+
+/// Draw the mark (or the preview of a mark) of a cell and the focus ring as vector shapes.
+fn draw_piece(b: &Button, state: &CellState, theme: &Theme) {
+    let size = f64::from(b.w().min(b.h()));
+    if b.has_focus() {
+        let inset = (size / 16.0) as i32;
+        draw::set_draw_color(b.label_color());
+        draw::set_line_style(draw::LineStyle::Dash, (size / 40.0).max(1.0) as i32);
+        draw::draw_rect(b.x() + inset, b.y() + inset, b.w() - 2 * inset, b.h() - 2 * inset);
+    }
+    if let (Field::Free, Some(overlay)) = (state.mark, &state.overlay) {
+        draw_overlay(b, overlay);
+    }
+    if draw_number(b, state, theme) {
+        draw::set_line_style(draw::LineStyle::Solid, 0);
+        return;
+    }
+    let (mark, color, scale) = if state.mark != Field::Free && (state.fading || state.dimmed) {
+        (state.mark, Color::color_average(piece_color(state.mark, theme), b.color(), 0.45), state.scale)
+    }
+    else if state.mark != Field::Free {
+        (state.mark, piece_color(state.mark, theme), state.scale)
+    }
+    else if state.hovered && state.ghost != Field::Free {
+        (state.ghost, Color::color_average(piece_color(state.ghost, theme), b.color(), 0.35), 1.0)
+    }
+    else {
+        draw::set_line_style(draw::LineStyle::Solid, 0);
+        return;
+    };
+    let center_x = f64::from(b.x()) + f64::from(b.w()) / 2.0;
+    let center_y = f64::from(b.y()) + f64::from(b.h()) / 2.0;
+    let half = size * 0.3 * scale;
+    draw::set_draw_color(color);
+    draw::set_line_style(draw::LineStyle::Solid | draw::LineStyle::CapRound, (size * theme.stroke_width * scale).max(1.0) as i32);
+    match mark {
+        Field::Cross => {
+            draw::draw_line((center_x - half) as i32, (center_y - half) as i32, (center_x + half) as i32, (center_y + half) as i32);
+            draw::draw_line((center_x + half) as i32, (center_y - half) as i32, (center_x - half) as i32, (center_y + half) as i32);
+        }
+        Field::Circle => {
+            draw::draw_arc((center_x - half) as i32, (center_y - half) as i32, (2.0 * half) as i32, (2.0 * half) as i32, 0.0, 360.0);
+        }
+        Field::Free => {}
+    }
+    draw::set_line_style(draw::LineStyle::Solid, 0);
 }
 
 impl Default for MyButton {
@@ -58,3 +353,558 @@ impl DerefMut for MyButton {
         &mut self.btn
     }
 }
+
+/// Return the value of a digit key (1 to 9) on the main keyboard or the numeric keypad.
+#[must_use]
+pub fn digit_key(key: Key) -> Option<usize> {
+    let bits = if key.bits() > Key::KP.bits() && key.bits() <= Key::KPLast.bits() {
+        key.bits() - Key::KP.bits()
+    }
+    else {
+        key.bits()
+    };
+    match u8::try_from(bits).map(char::from) {
+        Ok(digit @ '1'..='9') => digit.to_digit(10).map(|digit| digit as usize),
+        _ => None,
+    }
+}
+
+/// Grid of `MyButton`s for a board of `rows` x `columns` cells.
+///
+/// The buttons are stored in board order, but the first row of the board is displayed at the
+/// bottom like on a numeric keypad. Cells are kept square and centered whenever the grid is
+/// resized.
+#[derive(Clone)]
+pub struct BoardGrid {
+    group: Group,
+    buttons: Vec<MyButton>,
+    rows: usize,
+    columns: usize,
+    cell_size: Rc<Cell<i32>>,
+    theme: Rc<RefCell<Theme>>,
+    /// First and last cell of the winning line that is struck through.
+    strike: Rc<RefCell<Option<(usize, usize)>>>,
+}
+
+impl BoardGrid {
+    #[must_use]
+    pub fn new(x: i32, y: i32, w: i32, h: i32, rows: usize, columns: usize) -> BoardGrid {
+        let theme = Rc::new(RefCell::new(Theme::default()));
+        let group = Group::new(x, y, w, h, "");
+        let buttons = (0..rows * columns).map(|_| MyButton::with_theme(theme.clone())).collect();
+        group.end();
+        let mut grid = BoardGrid {
+            group,
+            buttons,
+            rows,
+            columns,
+            cell_size: Rc::new(Cell::new(0)),
+            theme,
+            strike: Rc::new(RefCell::new(None)),
+        };
+        grid.layout(x, y, w, h);
+        let mut resized_grid = grid.clone();
+        grid.group.resize_callback(move |_, x, y, w, h| resized_grid.layout(x, y, w, h));
+        let drawn_grid = grid.clone();
+        grid.group.draw(move |_| drawn_grid.draw_strike());
+        grid
+    }
+
+    /// Place the buttons as square cells in the center of the given area.
+    pub fn layout(&mut self, x: i32, y: i32, w: i32, h: i32) {
+        let rows = self.rows as i32;
+        let columns = self.columns as i32;
+        let cell_size = (w / columns).min(h / rows).max(1);
+        self.cell_size.set(cell_size);
+        let left = x + (w - cell_size * columns) / 2;
+        let top = y + (h - cell_size * rows) / 2;
+        for (i, but) in self.buttons.iter_mut().enumerate() {
+            let row = (i / self.columns) as i32;
+            let col = (i % self.columns) as i32;
+            but.resize(left + col * cell_size, top + (rows - 1 - row) * cell_size, cell_size, cell_size);
+        }
+        self.group.redraw();
+    }
+
+    /// Draw the line through the winning cells on top of the buttons.
+    fn draw_strike(&self) {
+        if let Some((first, last)) = *self.strike.borrow() {
+            let center = |but: &MyButton| (but.x() + but.w() / 2, but.y() + but.h() / 2);
+            let (x1, y1) = center(&self.buttons[first]);
+            let (x2, y2) = center(&self.buttons[last]);
+            draw::set_draw_color(Color::from_hex(self.theme.borrow().strike));
+            draw::set_line_style(draw::LineStyle::Solid | draw::LineStyle::CapRound, (self.cell_size.get() / 12).max(2));
+            draw::draw_line(x1, y1, x2, y2);
+            draw::set_line_style(draw::LineStyle::Solid, 0);
+        }
+    }
+
+    /// Show a mark in a cell. Placed marks grow to their full size in a short animation.
+    pub fn set_mark(&mut self, index: usize, mark: Field) {
+        let but = &mut self.buttons[index];
+        but.set_mark(mark);
+        if mark == Field::Free {
+            but.redraw();
+        }
+        else {
+            but.animate_mark();
+        }
+    }
+
+    /// Let a mark fall from the top of its column down to `index`, one row per frame. The
+    /// cells above `index` have to be free.
+    pub fn drop_mark(&mut self, index: usize, mark: Field) {
+        let (columns, target) = (self.columns, index / self.columns);
+        let column = index % columns;
+        let mut row = self.rows - 1;
+        let mut buttons = self.buttons.clone();
+        buttons[row * columns + column].set_mark(mark);
+        buttons[row * columns + column].redraw();
+        if row == target {
+            return;
+        }
+        app::add_timeout3(DROP_FRAME_TIME, move |handle| {
+            let current = row * columns + column;
+            // stop if the board was changed meanwhile, e.g. by a new game
+            if buttons[current].mark() != mark {
+                return;
+            }
+            buttons[current].set_mark(Field::Free);
+            buttons[current].redraw();
+            row -= 1;
+            buttons[current - columns].set_mark(mark);
+            buttons[current - columns].redraw();
+            if row > target {
+                app::repeat_timeout3(DROP_FRAME_TIME, handle);
+            }
+        });
+    }
+
+    /// Set the mark previewed when hovering a free cell, `Field::Free` for no preview.
+    pub fn set_ghost(&mut self, ghost: Field) {
+        for but in &mut self.buttons {
+            but.set_ghost(ghost);
+        }
+    }
+
+    /// Show a number in a cell. Placed numbers grow to their full size like marks.
+    pub fn set_number(&mut self, index: usize, number: Number) {
+        let but = &mut self.buttons[index];
+        but.set_number(number);
+        if number.is_none() {
+            but.redraw();
+        }
+        else {
+            but.animate_mark();
+        }
+    }
+
+    /// Show the numbers of `board` at once, without animations and highlights.
+    pub fn show_numbers(&mut self, board: &[Number]) {
+        for (but, number) in self.buttons.iter_mut().zip(board) {
+            but.set_number(*number);
+            but.set_highlight(false);
+        }
+        *self.strike.borrow_mut() = None;
+        self.group.redraw();
+    }
+
+    /// Set the number previewed when hovering a free cell, `None` for no preview.
+    pub fn set_ghost_number(&mut self, number: Number) {
+        for but in &mut self.buttons {
+            but.set_ghost_number(number);
+        }
+    }
+
+    /// Draw the mark on `cell` faded because it vanishes next, `None` to fade no mark.
+    pub fn set_fading(&mut self, cell: Option<usize>) {
+        for (index, but) in self.buttons.iter_mut().enumerate() {
+            if but.state.borrow().fading != (Some(index) == cell) {
+                but.set_fading(Some(index) == cell);
+            }
+        }
+    }
+
+    /// Grey out the whole board because it cannot be played any more.
+    pub fn set_dimmed(&mut self, dimmed: bool) {
+        for but in &mut self.buttons {
+            but.set_dimmed(dimmed);
+        }
+    }
+
+    /// Change the colours of all cells and pieces.
+    pub fn set_theme(&mut self, theme: &Theme) {
+        *self.theme.borrow_mut() = theme.clone();
+        for but in &mut self.buttons {
+            but.apply_theme();
+        }
+        self.group.redraw();
+    }
+
+    /// Highlight the cells of a winning line and strike them through.
+    pub fn highlight_line(&mut self, line: &[usize]) {
+        for cell in line {
+            self.buttons[*cell].set_highlight(true);
+        }
+        if let (Some(first), Some(last)) = (line.first(), line.last()) {
+            *self.strike.borrow_mut() = Some((*first, *last));
+        }
+        self.group.redraw();
+    }
+
+    /// Show the marks of `board` at once, without animations and highlights, e.g. to show an
+    /// earlier position of the game.
+    pub fn show_board(&mut self, board: &[Field]) {
+        for (but, mark) in self.buttons.iter_mut().zip(board) {
+            but.set_mark(*mark);
+            but.set_highlight(false);
+        }
+        *self.strike.borrow_mut() = None;
+        self.group.redraw();
+    }
+
+    /// Show the values of the cells as a heat map. Cells without a value (or beyond the end
+    /// of `overlays`) are shown as usual.
+    pub fn set_overlays(&mut self, overlays: &[Option<Overlay>]) {
+        for (i, but) in self.buttons.iter_mut().enumerate() {
+            but.set_overlay(overlays.get(i).cloned().flatten());
+        }
+    }
+
+    /// Remove all marks, highlights and overlays for a new game.
+    pub fn clear(&mut self) {
+        for but in &mut self.buttons {
+            but.set_mark(Field::Free);
+            but.set_highlight(false);
+            but.set_fading(false);
+            but.set_dimmed(false);
+            but.set_number(None);
+            but.set_overlay(None);
+        }
+        *self.strike.borrow_mut() = None;
+        self.group.redraw();
+    }
+
+    #[must_use]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    #[must_use]
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    #[must_use]
+    pub fn buttons(&self) -> &[MyButton] {
+        &self.buttons
+    }
+
+    pub fn buttons_mut(&mut self) -> &mut [MyButton] {
+        &mut self.buttons
+    }
+
+    /// Return the cell that has the keyboard focus.
+    #[must_use]
+    pub fn focused(&self) -> Option<usize> {
+        self.buttons.iter().position(|but| but.has_focus())
+    }
+
+    /// Give the keyboard focus (and thereby the focus ring) to a cell.
+    pub fn focus(&mut self, index: usize) {
+        if let Some(but) = self.buttons.get(index) {
+            app::set_focus(&**but);
+            self.group.redraw();
+        }
+    }
+
+    /// Move the focus `d_row` rows up and `d_col` columns right, staying on the board.
+    pub fn move_focus(&mut self, d_row: isize, d_col: isize) {
+        let current = self.focused().unwrap_or(0);
+        let row = (current / self.columns).saturating_add_signed(d_row).min(self.rows - 1);
+        let col = (current % self.columns).saturating_add_signed(d_col).min(self.columns - 1);
+        self.focus(row * self.columns + col);
+    }
+}
+
+impl Deref for BoardGrid {
+    type Target = Group;
+
+    fn deref(&self) -> &Self::Target {
+        &self.group
+    }
+}
+
+impl DerefMut for BoardGrid {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.group
+    }
+}
+
+/// Non-modal panel below the board with the state of the game, the score and feedback such as
+/// rejected moves.
+#[derive(Clone)]
+pub struct StatusPanel {
+    group: Group,
+    turn: Frame,
+    score: Frame,
+    message: Frame,
+}
+
+impl StatusPanel {
+    #[must_use]
+    pub fn new(x: i32, y: i32, w: i32, h: i32) -> StatusPanel {
+        let group = Group::new(x, y, w, h, "");
+        let line = || {
+            let mut frame = Frame::default();
+            frame.set_align(Align::Left | Align::Inside | Align::Clip);
+            frame
+        };
+        let mut panel = StatusPanel { turn: line(), score: line(), message: line(), group };
+        panel.group.end();
+        panel.layout(x, y, w, h);
+        let mut resized_panel = panel.clone();
+        panel.group.resize_callback(move |_, x, y, w, h| resized_panel.layout(x, y, w, h));
+        panel
+    }
+
+    /// Stack the three lines of text in the given area with a font size fitting their height.
+    pub fn layout(&mut self, x: i32, y: i32, w: i32, h: i32) {
+        let line_height = (h / 3).max(1);
+        let margin = line_height / 3;
+        for (i, frame) in [&mut self.turn, &mut self.score, &mut self.message].into_iter().enumerate() {
+            frame.resize(x + margin, y + i as i32 * line_height, w - 2 * margin, line_height);
+            frame.set_label_size((line_height * 11 / 20).max(8));
+        }
+        self.group.redraw();
+    }
+
+    /// Show whose turn it is or how the game ended.
+    pub fn set_turn(&mut self, text: &str) {
+        self.turn.set_label(text);
+        self.group.redraw();
+    }
+
+    pub fn set_score(&mut self, text: &str) {
+        self.score.set_label(text);
+        self.group.redraw();
+    }
+
+    /// Show feedback for the last action, an empty text removes it.
+    pub fn set_message(&mut self, text: &str) {
+        self.message.set_label(text);
+        self.group.redraw();
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.turn.set_label_color(Color::from_hex(theme.text));
+        self.score.set_label_color(Color::from_hex(theme.text));
+        self.message.set_label_color(Color::from_hex(theme.strike));
+        self.group.redraw();
+    }
+}
+
+impl Deref for StatusPanel {
+    type Target = Group;
+
+    fn deref(&self) -> &Self::Target {
+        &self.group
+    }
+}
+
+impl DerefMut for StatusPanel {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.group
+    }
+}
+
+/// Scrollable list of the moves of a game, starting with the empty board.
+#[derive(Clone)]
+pub struct MoveList {
+    browser: HoldBrowser,
+    /// The browser has no text colour of its own, so every entry starts with a format code
+    /// for this colour.
+    text_color: Rc<Cell<Color>>,
+}
+
+impl MoveList {
+    #[must_use]
+    pub fn new(x: i32, y: i32, w: i32, h: i32) -> MoveList {
+        let mut list = MoveList {
+            browser: HoldBrowser::new(x, y, w, h, ""),
+            text_color: Rc::new(Cell::new(Color::from_hex(Theme::default().text))),
+        };
+        list.set_moves(&[], 0);
+        list.layout(x, y, w, h);
+        let mut resized_list = list.clone();
+        list.browser.resize_callback(move |_, x, y, w, h| resized_list.layout(x, y, w, h));
+        list
+    }
+
+    /// Adapt the font size to the width of the list.
+    pub fn layout(&mut self, _x: i32, _y: i32, w: i32, _h: i32) {
+        self.browser.set_text_size((w / 9).clamp(10, 24));
+        self.browser.redraw();
+    }
+
+    /// Add an entry in the text colour, `@.` ends the format codes.
+    fn add_entry(&mut self, text: &str) {
+        self.browser.add(&format!("@C{}@.{text}", self.text_color.get().bits()));
+    }
+
+    /// Show the moves, e.g. `X b2`, and select the entry after `selected` moves (0 for the
+    /// empty board).
+    pub fn set_moves(&mut self, moves: &[String], selected: usize) {
+        self.browser.clear();
+        self.add_entry("Start");
+        for (number, text) in moves.iter().enumerate() {
+            self.add_entry(&format!("{}. {text}", number + 1));
+        }
+        let line = (selected.min(moves.len()) + 1) as i32;
+        self.browser.select(line);
+        self.browser.make_visible(line);
+        self.browser.redraw();
+    }
+
+    /// Return the number of moves up to the selected entry.
+    #[must_use]
+    pub fn selected(&self) -> Option<usize> {
+        usize::try_from(self.browser.value() - 1).ok()
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.browser.set_color(Color::from_hex(theme.cell));
+        self.browser.set_selection_color(Color::from_hex(theme.highlight));
+        self.text_color.set(Color::from_hex(theme.text));
+        for line in 1..=self.browser.size() {
+            if let Some(text) = self.browser.text(line) {
+                let entry = text.split_once("@.").map_or(text.as_str(), |(_, entry)| entry);
+                let entry = format!("@C{}@.{entry}", self.text_color.get().bits());
+                self.browser.set_text(line, &entry);
+            }
+        }
+        self.browser.redraw();
+    }
+}
+
+impl Deref for MoveList {
+    type Target = HoldBrowser;
+
+    fn deref(&self) -> &Self::Target {
+        &self.browser
+    }
+}
+
+impl DerefMut for MoveList {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.browser
+    }
+}
+
+/// Range of the speed slider of the `WatchPanel` in moves per second.
+pub const WATCH_SPEEDS: (f64, f64) = (0.5, 10.0);
+
+/// Controls for watching the AI play against itself: the strategy of each side, the speed
+/// and buttons to pause and to step through the game move by move.
+#[derive(Clone)]
+pub struct WatchPanel {
+    group: Group,
+    captions: [Frame; 3],
+    crosses: Choice,
+    circles: Choice,
+    speed: HorNiceSlider,
+    pause: Button,
+    step: Button,
+}
+
+impl WatchPanel {
+    /// Create the panel. Its controls send `SetStrategy`, `SetSpeed`, `TogglePause` and
+    /// `Step` messages through `s`.
+    #[must_use]
+    pub fn new(x: i32, y: i32, w: i32, h: i32, strategies: &[&str], speed: f64, s: app::Sender<Message>) -> WatchPanel {
+        let group = Group::new(x, y, w, h, "");
+        let caption = |text: &str| {
+            let mut frame = Frame::default().with_label(text);
+            frame.set_align(Align::Left | Align::Inside);
+            frame
+        };
+        let captions = [caption("X"), caption("O"), caption("Speed")];
+        let strategy_choice = |player: Field| {
+            let mut choice = Choice::default();
+            choice.add_choice(&strategies.join("|"));
+            choice.set_value(0);
+            choice.set_callback(move |choice| s.send(Message::SetStrategy(player, choice.value())));
+            choice
+        };
+        let crosses = strategy_choice(Field::Cross);
+        let circles = strategy_choice(Field::Circle);
+        let mut slider = HorNiceSlider::default();
+        slider.set_range(WATCH_SPEEDS.0, WATCH_SPEEDS.1);
+        slider.set_value(speed);
+        slider.set_callback(move |slider| s.send(Message::SetSpeed(slider.value())));
+        let mut pause = Button::default().with_label("Pause");
+        pause.emit(s, Message::TogglePause);
+        let mut step = Button::default().with_label("Step");
+        step.emit(s, Message::Step);
+        group.end();
+        let mut panel = WatchPanel { group, captions, crosses, circles, speed: slider, pause, step };
+        panel.layout(x, y, w, h);
+        let mut resized_panel = panel.clone();
+        panel.group.resize_callback(move |_, x, y, w, h| resized_panel.layout(x, y, w, h));
+        panel
+    }
+
+    /// Put a caption and a control in each of the first three rows and the buttons next to
+    /// each other in the last row.
+    pub fn layout(&mut self, x: i32, y: i32, w: i32, h: i32) {
+        let row_h = (h / 4).max(1);
+        let caption_w = w / 3;
+        let label_size = (row_h / 2).clamp(8, 18);
+        let controls: [&mut dyn WidgetExt; 3] = [&mut self.crosses, &mut self.circles, &mut self.speed];
+        for (row, (caption, control)) in self.captions.iter_mut().zip(controls).enumerate() {
+            let row_y = y + row as i32 * row_h;
+            caption.resize(x, row_y, caption_w, row_h);
+            caption.set_label_size(label_size);
+            control.resize(x + caption_w, row_y + row_h / 8, w - caption_w, row_h * 3 / 4);
+        }
+        self.crosses.set_text_size(label_size);
+        self.circles.set_text_size(label_size);
+        for (i, button) in [&mut self.pause, &mut self.step].into_iter().enumerate() {
+            button.resize(x + i as i32 * (w / 2), y + 3 * row_h + row_h / 8, w / 2, row_h * 3 / 4);
+            button.set_label_size(label_size);
+        }
+        self.group.redraw();
+    }
+
+    /// Label the pause button with what it does next.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.pause.set_label(if paused { "Resume" } else { "Pause" });
+        if paused {
+            self.step.activate();
+        }
+        else {
+            self.step.deactivate();
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        for caption in &mut self.captions {
+            caption.set_label_color(Color::from_hex(theme.text));
+        }
+        self.group.redraw();
+    }
+}
+
+impl Deref for WatchPanel {
+    type Target = Group;
+
+    fn deref(&self) -> &Self::Target {
+        &self.group
+    }
+}
+
+impl DerefMut for WatchPanel {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.group
+    }
+}
@@ -0,0 +1,144 @@
+use fltk::{app, enums::{Color, Event}, group::{Group, Scroll, ScrollType}, prelude::*};
+use std::cell::Cell;
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+use crate::tic_tac_toe::{Field, VariantMessage};
+use crate::tic_tac_toe::gomoku::{Gomoku, SIZE};
+use crate::tic_tac_toe::variant::Variant;
+use super::{BoardGrid, Theme, VariantSender, VariantView};
+
+/// Factor the board grows or shrinks by with every zoom step.
+const ZOOM_STEP: f64 = 1.25;
+/// Largest zoom, the smallest one shows the whole board.
+const MAX_ZOOM: f64 = 4.0;
+
+/// The 15x15 Gomoku board in a scrollable area that can be zoomed with Ctrl and the mouse
+/// wheel or the `+` and `-` keys.
+#[derive(Clone)]
+pub struct GomokuGrid {
+    group: Group,
+    scroll: Scroll,
+    grid: BoardGrid,
+    /// Size of the board relative to the visible area, 1.0 shows the whole board.
+    zoom: Rc<Cell<f64>>,
+}
+
+impl GomokuGrid {
+    pub fn layout(&mut self, x: i32, y: i32, w: i32, h: i32) {
+        self.scroll.resize(x, y, w, h);
+        self.zoom_at(1.0, x + w / 2, y + h / 2);
+    }
+
+    /// Size of the board at the current zoom.
+    fn board_size(&self) -> i32 {
+        (f64::from(self.scroll.w().min(self.scroll.h())) * self.zoom.get()) as i32
+    }
+
+    /// Change the zoom by `factor`, keeping the point `(x, y)` of the window in place.
+    fn zoom_at(&mut self, factor: f64, x: i32, y: i32) {
+        let old_size = self.grid.w().max(1);
+        self.zoom.set((self.zoom.get() * factor).clamp(1.0, MAX_ZOOM));
+        let size = self.board_size();
+        let (w, h) = (self.scroll.w(), self.scroll.h());
+        // a board smaller than the area is centered, a larger one starts at the edge
+        let left = ((w - size) / 2).max(0);
+        let top = ((h - size) / 2).max(0);
+        let scale = f64::from(size) / f64::from(old_size);
+        let new_x = (f64::from(x - self.grid.x()) * scale) as i32 + left - (x - self.scroll.x());
+        let new_y = (f64::from(y - self.grid.y()) * scale) as i32 + top - (y - self.scroll.y());
+        // the scroll area moves its children itself when it scrolls
+        let (old_x, old_y) = (self.scroll.xposition(), self.scroll.yposition());
+        self.grid.resize(self.scroll.x() + left - old_x, self.scroll.y() + top - old_y, size, size);
+        self.scroll.scroll_to(new_x.clamp(0, (size - w).max(0)), new_y.clamp(0, (size - h).max(0)));
+        self.scroll.redraw();
+    }
+
+    /// Scroll so that a cell is visible, if it is not.
+    fn show_cell(&mut self, cell: usize) {
+        let but = &self.grid.buttons()[cell];
+        let inside_x = but.x() >= self.scroll.x() && but.x() + but.w() <= self.scroll.x() + self.scroll.w();
+        let inside_y = but.y() >= self.scroll.y() && but.y() + but.h() <= self.scroll.y() + self.scroll.h();
+        if !inside_x || !inside_y {
+            let (x, y) = (but.x() + but.w() / 2, but.y() + but.h() / 2);
+            let (center_x, center_y) = (self.scroll.x() + self.scroll.w() / 2, self.scroll.y() + self.scroll.h() / 2);
+            let size = self.grid.w();
+            let new_x = self.scroll.xposition() + x - center_x;
+            let new_y = self.scroll.yposition() + y - center_y;
+            self.scroll.scroll_to(new_x.clamp(0, (size - self.scroll.w()).max(0)), new_y.clamp(0, (size - self.scroll.h()).max(0)));
+            self.scroll.redraw();
+        }
+    }
+}
+
+impl VariantView<Gomoku> for GomokuGrid {
+    fn new(x: i32, y: i32, w: i32, h: i32, _game: &Gomoku, s: VariantSender) -> GomokuGrid {
+        let group = Group::new(x, y, w, h, "");
+        let mut scroll = Scroll::new(x, y, w, h, "");
+        scroll.set_type(ScrollType::Both);
+        let mut grid = BoardGrid::new(x, y, w.min(h), w.min(h), SIZE, SIZE);
+        scroll.end();
+        group.end();
+        for (cell, but) in grid.buttons_mut().iter_mut().enumerate() {
+            but.emit(s.sender(), s.message(VariantMessage::Number(cell)));
+        }
+        let mut view = GomokuGrid { group, scroll, grid, zoom: Rc::new(Cell::new(1.0)) };
+        view.layout(x, y, w, h);
+        let mut resized_view = view.clone();
+        view.group.resize_callback(move |_, x, y, w, h| resized_view.layout(x, y, w, h));
+        let mut zoomed_view = view.clone();
+        view.scroll.handle(move |scroll, event| {
+            let (center_x, center_y) = (scroll.x() + scroll.w() / 2, scroll.y() + scroll.h() / 2);
+            match event {
+                Event::MouseWheel if app::is_event_ctrl() => match app::event_dy() {
+                    app::MouseWheel::Up => zoomed_view.zoom_at(ZOOM_STEP, app::event_x(), app::event_y()),
+                    app::MouseWheel::Down => zoomed_view.zoom_at(1.0 / ZOOM_STEP, app::event_x(), app::event_y()),
+                    _ => return false,
+                },
+                Event::KeyDown => match app::event_text().as_str() {
+                    "+" | "=" => zoomed_view.zoom_at(ZOOM_STEP, center_x, center_y),
+                    "-" => zoomed_view.zoom_at(1.0 / ZOOM_STEP, center_x, center_y),
+                    _ => return false,
+                },
+                _ => return false,
+            }
+            true
+        });
+        view
+    }
+
+    fn show(&mut self, game: &Gomoku, last: Option<usize>, ghost: Field) {
+        self.grid.show_board(game.board());
+        if let Some(line) = game.winning_line() {
+            self.grid.highlight_line(line);
+        }
+        self.grid.set_ghost(ghost);
+        match last {
+            Some(mv) => {
+                self.grid.set_mark(mv, game.board()[mv]);
+                self.show_cell(mv);
+            }
+            None if game.history().is_empty() => self.show_cell(Gomoku::dims().center()),
+            None => {}
+        }
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.grid.set_theme(theme);
+        self.scroll.set_color(Color::from_hex(theme.window));
+        self.group.redraw();
+    }
+}
+
+impl Deref for GomokuGrid {
+    type Target = Group;
+
+    fn deref(&self) -> &Self::Target {
+        &self.group
+    }
+}
+
+impl DerefMut for GomokuGrid {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.group
+    }
+}
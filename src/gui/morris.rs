@@ -0,0 +1,100 @@
+use fltk::{group::Group, prelude::*};
+use std::cell::Cell;
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+use crate::tic_tac_toe::{Dimensions, Field, VariantMessage};
+use crate::tic_tac_toe::morris::{move_cells, slide, Morris};
+use crate::tic_tac_toe::variant::Variant;
+use super::{BoardGrid, Theme, VariantSender, VariantView};
+
+/// The Three Men's Morris board. Once all marks are placed, a move takes two clicks: one on
+/// the mark to move, which is highlighted, and one on its new cell.
+#[derive(Clone)]
+pub struct MorrisGrid {
+    grid: BoardGrid,
+    /// Cell of the mark that was selected to be moved.
+    selected: Rc<Cell<Option<usize>>>,
+}
+
+impl MorrisGrid {
+    /// Highlight the selected mark and preview it on the cells it can move to, or forget the
+    /// selection with `None`.
+    fn select(&mut self, cell: Option<usize>, player: Field) {
+        if let Some(old) = self.selected.replace(cell) {
+            self.grid.buttons_mut()[old].set_highlight(false);
+        }
+        self.grid.set_ghost(Field::Free);
+        if let Some(cell) = cell {
+            self.grid.buttons_mut()[cell].set_highlight(true);
+            for to in Dimensions::default().adjacent(cell) {
+                self.grid.buttons_mut()[to].set_ghost(player);
+            }
+        }
+    }
+}
+
+impl VariantView<Morris> for MorrisGrid {
+    fn new(x: i32, y: i32, w: i32, h: i32, _game: &Morris, s: VariantSender) -> MorrisGrid {
+        let mut grid = BoardGrid::new(x, y, w, h, 3, 3);
+        for (cell, but) in grid.buttons_mut().iter_mut().enumerate() {
+            but.emit(s.sender(), s.message(VariantMessage::Number(cell)));
+        }
+        MorrisGrid { grid, selected: Rc::new(Cell::new(None)) }
+    }
+
+    fn click(&mut self, game: &Morris, cell: usize) -> Option<usize> {
+        if game.placing() || game.outcome().is_some() {
+            return Some(cell);
+        }
+        let player = game.to_move();
+        match self.selected.get() {
+            // a click on the selected mark puts it down again
+            Some(selected) if selected == cell => {
+                self.select(None, player);
+                None
+            }
+            _ if game.board()[cell] == player => {
+                self.select(Some(cell), player);
+                None
+            }
+            Some(selected) => Some(slide(selected, cell)),
+            // without a selected mark, the game explains what to do
+            None => Some(cell),
+        }
+    }
+
+    fn show(&mut self, game: &Morris, last: Option<usize>, ghost: Field) {
+        self.select(None, ghost);
+        self.grid.show_board(game.board());
+        // while the marks are moved, the player has to select one first
+        self.grid.set_ghost(if game.placing() { ghost } else { Field::Free });
+        if let Some(mv) = last {
+            let (from, to) = move_cells(mv);
+            if let Some(from) = from {
+                self.grid.set_mark(from, Field::Free);
+            }
+            self.grid.set_mark(to, game.board()[to]);
+        }
+        if let Some(line) = game.winning_line() {
+            self.grid.highlight_line(&line);
+        }
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.grid.set_theme(theme);
+    }
+}
+
+impl Deref for MorrisGrid {
+    type Target = Group;
+
+    fn deref(&self) -> &Self::Target {
+        &self.grid
+    }
+}
+
+impl DerefMut for MorrisGrid {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.grid
+    }
+}
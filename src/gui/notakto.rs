@@ -0,0 +1,106 @@
+use fltk::{draw, enums::{Align, Color}, group::Group, prelude::*};
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+use crate::tic_tac_toe::{Field, VariantMessage};
+use crate::tic_tac_toe::notakto::{Notakto, CELLS};
+use super::{BoardGrid, Theme, VariantSender, VariantView};
+
+/// The boards of Notakto side by side, the dead ones greyed out with the line that killed
+/// them highlighted.
+#[derive(Clone)]
+pub struct NotaktoGrid {
+    group: Group,
+    boards: Vec<BoardGrid>,
+    theme: Rc<RefCell<Theme>>,
+}
+
+impl NotaktoGrid {
+    /// Place the boards in a row in the center of the given area, with their names above.
+    pub fn layout(&mut self, x: i32, y: i32, w: i32, h: i32) {
+        let count = self.boards.len() as i32;
+        let gap = w / 40;
+        let caption_h = h / 8;
+        let size = ((w - (count + 1) * gap) / count).min(h - caption_h).max(1);
+        let left = x + (w - count * (size + gap) + gap) / 2;
+        let top = y + caption_h + (h - caption_h - size) / 2;
+        for (index, board) in self.boards.iter_mut().enumerate() {
+            board.resize(left + index as i32 * (size + gap), top, size, size);
+        }
+        self.group.redraw();
+    }
+
+    fn draw_captions(&self) {
+        let theme = self.theme.borrow();
+        let caption_h = self.group.h() / 8;
+        draw::set_draw_color(Color::from_hex(theme.text));
+        draw::set_font(draw::font(), (caption_h / 2).max(8));
+        for (index, board) in self.boards.iter().enumerate() {
+            draw::draw_text2(&format!("Board {}", index + 1), board.x(), board.y() - caption_h, board.w(), caption_h, Align::Center);
+        }
+    }
+}
+
+impl VariantView<Notakto> for NotaktoGrid {
+    const ASPECT: f64 = 3.3;
+
+    fn new(x: i32, y: i32, w: i32, h: i32, game: &Notakto, s: VariantSender) -> NotaktoGrid {
+        let group = Group::new(x, y, w, h, "");
+        let boards: Vec<BoardGrid> = (0..game.board_count()).map(|_| BoardGrid::new(x, y, w / 3, h, 3, 3)).collect();
+        group.end();
+        let mut grid = NotaktoGrid { group, boards, theme: Rc::new(RefCell::new(Theme::default())) };
+        for (index, board) in grid.boards.iter_mut().enumerate() {
+            for (cell, but) in board.buttons_mut().iter_mut().enumerate() {
+                but.emit(s.sender(), s.message(VariantMessage::Number(index * CELLS + cell)));
+            }
+        }
+        grid.layout(x, y, w, h);
+        let mut resized_grid = grid.clone();
+        grid.group.resize_callback(move |_, x, y, w, h| resized_grid.layout(x, y, w, h));
+        let drawn_grid = grid.clone();
+        grid.group.draw(move |_| drawn_grid.draw_captions());
+        grid
+    }
+
+    fn show(&mut self, game: &Notakto, last: Option<usize>, ghost: Field) {
+        for (index, board) in self.boards.iter_mut().enumerate() {
+            board.show_board(game.board(index));
+            if let Some(line) = game.dead_line(index) {
+                board.set_dimmed(true);
+                board.set_ghost(Field::Free);
+                board.highlight_line(&line);
+            }
+            else {
+                board.set_dimmed(false);
+                // both players place crosses
+                board.set_ghost(if ghost == Field::Free { Field::Free } else { Field::Cross });
+            }
+        }
+        if let Some(mv) = last {
+            self.boards[mv / CELLS].set_mark(mv % CELLS, Field::Cross);
+        }
+        self.group.redraw();
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        *self.theme.borrow_mut() = theme.clone();
+        for board in &mut self.boards {
+            board.set_theme(theme);
+        }
+        self.group.redraw();
+    }
+}
+
+impl Deref for NotaktoGrid {
+    type Target = Group;
+
+    fn deref(&self) -> &Self::Target {
+        &self.group
+    }
+}
+
+impl DerefMut for NotaktoGrid {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.group
+    }
+}
@@ -0,0 +1,130 @@
+use fltk::{button::RadioButton, enums::{Color, FrameType, Shortcut}, group::Group, prelude::*};
+use std::cell::Cell;
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+use crate::tic_tac_toe::{Field, VariantMessage};
+use crate::tic_tac_toe::numerical::{move_number, number_move, Numerical};
+use super::{BoardGrid, Theme, VariantSender, VariantView};
+
+/// The board of Numerical tic-tac-toe with a picker below it for the number the next move
+/// places, which can also be chosen with the number keys. Only the numbers the player to move
+/// has left can be picked.
+#[derive(Clone)]
+pub struct NumericalGrid {
+    group: Group,
+    grid: BoardGrid,
+    pickers: Vec<RadioButton>,
+    /// Number placed by a click on the board.
+    number: Rc<Cell<u8>>,
+    /// Whether the number is previewed on the free cells.
+    ghost: Rc<Cell<bool>>,
+}
+
+impl NumericalGrid {
+    /// Place the board above the picker, which gets an eighth of the height.
+    pub fn layout(&mut self, x: i32, y: i32, w: i32, h: i32) {
+        let picker_h = h / 8;
+        let button_w = (w / 10).min(picker_h);
+        let left = x + (w - button_w * self.pickers.len() as i32) / 2;
+        self.grid.resize(x, y, w, h - picker_h);
+        for (index, picker) in self.pickers.iter_mut().enumerate() {
+            picker.resize(left + index as i32 * button_w, y + h - picker_h + picker_h / 8, button_w, picker_h * 3 / 4);
+        }
+        self.group.redraw();
+    }
+
+    /// Choose the number the next click places.
+    fn select(&mut self, number: u8) {
+        self.number.set(number);
+        if self.ghost.get() {
+            self.grid.set_ghost_number(Some(number));
+        }
+    }
+}
+
+impl VariantView<Numerical> for NumericalGrid {
+    const ASPECT: f64 = 0.9;
+
+    fn new(x: i32, y: i32, w: i32, h: i32, _game: &Numerical, s: VariantSender) -> NumericalGrid {
+        let group = Group::new(x, y, w, h, "");
+        let mut grid = BoardGrid::new(x, y, w, h, 3, 3);
+        let pickers: Vec<RadioButton> = (1..=9).map(|number: u8| RadioButton::new(x, y, w / 10, h / 8, None).with_label(&number.to_string())).collect();
+        group.end();
+        for (cell, but) in grid.buttons_mut().iter_mut().enumerate() {
+            but.emit(s.sender(), s.message(VariantMessage::Number(cell)));
+        }
+        let mut view = NumericalGrid { group, grid, pickers, number: Rc::new(Cell::new(1)), ghost: Rc::new(Cell::new(false)) };
+        for (index, number) in (1..=9).enumerate() {
+            let mut selected_view = view.clone();
+            let picker = &mut view.pickers[index];
+            picker.set_frame(FrameType::GleamUpBox);
+            picker.set_shortcut(Shortcut::None | char::from(b'0' + number));
+            picker.set_callback(move |_| selected_view.select(number));
+        }
+        view.pickers[0].toggle(true);
+        view.layout(x, y, w, h);
+        let mut resized_view = view.clone();
+        view.group.resize_callback(move |_, x, y, w, h| resized_view.layout(x, y, w, h));
+        view
+    }
+
+    fn click(&mut self, _game: &Numerical, cell: usize) -> Option<usize> {
+        Some(number_move(cell, self.number.get()))
+    }
+
+    /// Only the numbers left to the player to move can be picked; if the picked one is gone,
+    /// the smallest one left is picked instead. `ghost` only tells whether the player to move
+    /// places a number, the preview shows the picked one.
+    fn show(&mut self, game: &Numerical, last: Option<usize>, ghost: Field) {
+        let available = game.available_numbers();
+        if !available.contains(&self.number.get()) {
+            if let Some(first) = available.first() {
+                self.number.set(*first);
+            }
+        }
+        for (number, picker) in (1..=9).zip(self.pickers.iter_mut()) {
+            if available.contains(&number) {
+                picker.activate();
+            }
+            else {
+                picker.deactivate();
+            }
+            picker.toggle(number == self.number.get());
+        }
+        self.ghost.set(ghost != Field::Free);
+        self.grid.show_numbers(game.board());
+        self.grid.set_ghost_number(if ghost == Field::Free { None } else { Some(self.number.get()) });
+        if let Some(mv) = last {
+            let (cell, number) = move_number(mv);
+            self.grid.set_number(cell, Some(number));
+        }
+        if let Some(line) = game.winning_line() {
+            self.grid.highlight_line(&line);
+        }
+        self.group.redraw();
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.grid.set_theme(theme);
+        for picker in &mut self.pickers {
+            picker.set_color(Color::from_hex(theme.cell));
+            picker.set_selection_color(Color::from_hex(theme.highlight));
+            picker.set_label_color(Color::from_hex(theme.text));
+        }
+        self.group.redraw();
+    }
+}
+
+impl Deref for NumericalGrid {
+    type Target = Group;
+
+    fn deref(&self) -> &Self::Target {
+        &self.group
+    }
+}
+
+impl DerefMut for NumericalGrid {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.group
+    }
+}
@@ -0,0 +1,107 @@
+use fltk::{draw, enums::{Align, Color}, group::Group, prelude::*};
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+use crate::tic_tac_toe::{Field, VariantMessage};
+use crate::tic_tac_toe::qubic::{Qubic, LAYER, SIZE};
+use super::{BoardGrid, Theme, VariantSender, VariantView};
+
+/// The four layers of the Qubic cube side by side, the bottom layer on the left.
+#[derive(Clone)]
+pub struct QubicGrid {
+    group: Group,
+    layers: Vec<BoardGrid>,
+    theme: Rc<RefCell<Theme>>,
+}
+
+impl QubicGrid {
+    /// Place the layers in a row in the center of the given area, with their names above.
+    pub fn layout(&mut self, x: i32, y: i32, w: i32, h: i32) {
+        let gap = w / 40;
+        let caption_h = h / 8;
+        let size = ((w - 5 * gap) / SIZE as i32).min(h - caption_h).max(1);
+        let left = x + (w - SIZE as i32 * (size + gap) + gap) / 2;
+        let top = y + caption_h + (h - caption_h - size) / 2;
+        for (index, layer) in self.layers.iter_mut().enumerate() {
+            layer.resize(left + index as i32 * (size + gap), top, size, size);
+        }
+        self.group.redraw();
+    }
+
+    fn draw_captions(&self) {
+        let theme = self.theme.borrow();
+        let caption_h = self.group.h() / 8;
+        draw::set_draw_color(Color::from_hex(theme.text));
+        draw::set_font(draw::font(), (caption_h / 2).max(8));
+        for (index, layer) in self.layers.iter().enumerate() {
+            draw::draw_text2(&format!("Layer {}", index + 1), layer.x(), layer.y() - caption_h, layer.w(), caption_h, Align::Center);
+        }
+    }
+}
+
+impl VariantView<Qubic> for QubicGrid {
+    const ASPECT: f64 = 4.4;
+
+    fn new(x: i32, y: i32, w: i32, h: i32, _game: &Qubic, s: VariantSender) -> QubicGrid {
+        let group = Group::new(x, y, w, h, "");
+        let layers: Vec<BoardGrid> = (0..SIZE).map(|_| BoardGrid::new(x, y, w / 4, h, SIZE, SIZE)).collect();
+        group.end();
+        let mut grid = QubicGrid { group, layers, theme: Rc::new(RefCell::new(Theme::default())) };
+        for (index, layer) in grid.layers.iter_mut().enumerate() {
+            for (cell, but) in layer.buttons_mut().iter_mut().enumerate() {
+                but.emit(s.sender(), s.message(VariantMessage::Number(index * LAYER + cell)));
+            }
+        }
+        grid.layout(x, y, w, h);
+        let mut resized_grid = grid.clone();
+        grid.group.resize_callback(move |_, x, y, w, h| resized_grid.layout(x, y, w, h));
+        let drawn_grid = grid.clone();
+        grid.group.draw(move |_| drawn_grid.draw_captions());
+        grid
+    }
+
+    fn show(&mut self, game: &Qubic, last: Option<usize>, ghost: Field) {
+        for (index, layer) in self.layers.iter_mut().enumerate() {
+            layer.show_board(game.cube().layer(index));
+            layer.set_ghost(ghost);
+        }
+        if let Some(mv) = last {
+            self.layers[mv / LAYER].set_mark(mv % LAYER, game.cube().cells()[mv]);
+        }
+        if let Some(line) = game.winning_line() {
+            if line.iter().all(|cell| cell / LAYER == line[0] / LAYER) {
+                // a line within a layer is struck through like on the flat boards
+                let cells: Vec<usize> = line.iter().map(|cell| cell % LAYER).collect();
+                self.layers[line[0] / LAYER].highlight_line(&cells);
+            }
+            else {
+                for cell in line {
+                    self.layers[cell / LAYER].buttons_mut()[cell % LAYER].set_highlight(true);
+                }
+            }
+        }
+        self.group.redraw();
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        *self.theme.borrow_mut() = theme.clone();
+        for layer in &mut self.layers {
+            layer.set_theme(theme);
+        }
+        self.group.redraw();
+    }
+}
+
+impl Deref for QubicGrid {
+    type Target = Group;
+
+    fn deref(&self) -> &Self::Target {
+        &self.group
+    }
+}
+
+impl DerefMut for QubicGrid {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.group
+    }
+}
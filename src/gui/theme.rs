@@ -0,0 +1,239 @@
+use std::path::PathBuf;
+
+/// Environment variable with the path of a user theme file, e.g.
+/// `TIC_TAC_TOE_THEME=~/my_theme.txt`.
+pub const THEME_VARIABLE: &str = "TIC_TAC_TOE_THEME";
+
+/// Colours (as `0xRRGGBB`) and stroke width used to draw the window and the board.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub window: u32,
+    pub cell: u32,
+    pub cell_hover: u32,
+    pub cell_pressed: u32,
+    /// Background of the cells of the winning line.
+    pub highlight: u32,
+    /// Line drawn through the winning cells.
+    pub strike: u32,
+    /// Labels and the focus ring.
+    pub text: u32,
+    pub cross: u32,
+    pub circle: u32,
+    /// Width of the strokes of the pieces as a fraction of the cell size.
+    pub stroke_width: f64,
+}
+
+impl Theme {
+    #[must_use]
+    pub fn dark() -> Theme {
+        Theme {
+            name: "Dark".to_string(),
+            window: 0x33_3333,
+            cell: 0x42_4242,
+            cell_hover: 0x2b_2b2b,
+            cell_pressed: 0x1b_1b1b,
+            highlight: 0x2e_7d32,
+            strike: 0xff_d54f,
+            text: 0xff_ffff,
+            cross: 0x4f_c3f7,
+            circle: 0xff_b74d,
+            stroke_width: 0.08,
+        }
+    }
+
+    #[must_use]
+    pub fn light() -> Theme {
+        Theme {
+            name: "Light".to_string(),
+            window: 0xf0_f0f0,
+            cell: 0xfa_fafa,
+            cell_hover: 0xe0_e0e0,
+            cell_pressed: 0xcf_cfcf,
+            highlight: 0xa5_d6a7,
+            strike: 0x2e_7d32,
+            text: 0x21_2121,
+            cross: 0x15_65c0,
+            circle: 0xc6_2828,
+            stroke_width: 0.08,
+        }
+    }
+
+    #[must_use]
+    pub fn high_contrast() -> Theme {
+        Theme {
+            name: "High contrast".to_string(),
+            window: 0x00_0000,
+            cell: 0x00_0000,
+            cell_hover: 0x40_4040,
+            cell_pressed: 0x60_6060,
+            highlight: 0x00_6400,
+            strike: 0xff_ffff,
+            text: 0xff_ffff,
+            cross: 0xff_ff00,
+            circle: 0x00_ffff,
+            stroke_width: 0.12,
+        }
+    }
+
+    /// Uses the Okabe-Ito palette, which stays distinguishable for all common forms of colour
+    /// blindness.
+    #[must_use]
+    pub fn colourblind_safe() -> Theme {
+        Theme {
+            name: "Colourblind safe".to_string(),
+            window: 0x33_3333,
+            cell: 0x42_4242,
+            cell_hover: 0x2b_2b2b,
+            cell_pressed: 0x1b_1b1b,
+            highlight: 0x00_9e73,
+            strike: 0xf0_e442,
+            text: 0xff_ffff,
+            cross: 0x56_b4e9,
+            circle: 0xe6_9f00,
+            stroke_width: 0.1,
+        }
+    }
+
+    /// All built-in themes, the first one is the default.
+    #[must_use]
+    pub fn built_in() -> Vec<Theme> {
+        vec![Theme::dark(), Theme::light(), Theme::high_contrast(), Theme::colourblind_safe()]
+    }
+
+    /// Parse a theme file.
+    ///
+    /// Every line has the form `key = value`, lines starting with `#` are comments. Colours are
+    /// given as `#RRGGBB`. The optional key `base` names the built-in theme that provides all
+    /// values missing from the file (default: dark). Example:
+    ///
+    /// ```text
+    /// name = Solarized
+    /// base = light
+    /// window = #fdf6e3
+    /// cross = #268bd2
+    /// circle = #dc322f
+    /// stroke_width = 0.1
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a message naming the offending line if a line, key or value is invalid.
+    pub fn parse(text: &str) -> Result<Theme, String> {
+        let entries = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(number, line)| match line.split_once('=') {
+                Some((key, value)) => Ok((number, key.trim(), value.trim())),
+                None => Err(format!("line {number}: expected `key = value`")),
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let mut theme = match entries.iter().find(|(_, key, _)| *key == "base") {
+            Some((number, _, base)) => Theme::built_in()
+                .into_iter()
+                .find(|theme| theme.name.eq_ignore_ascii_case(base) || theme.name.replace(' ', "_").eq_ignore_ascii_case(base))
+                .ok_or_else(|| format!("line {number}: unknown base theme `{base}`"))?,
+            None => Theme::dark(),
+        };
+        theme.name = "Custom".to_string();
+        for (number, key, value) in entries {
+            let colour = match key {
+                "base" => continue,
+                "name" => {
+                    theme.name = value.to_string();
+                    continue;
+                }
+                "stroke_width" => {
+                    theme.stroke_width = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|width| *width > 0.0 && *width < 0.5)
+                        .ok_or_else(|| format!("line {number}: stroke width must be a number between 0 and 0.5"))?;
+                    continue;
+                }
+                "window" => &mut theme.window,
+                "cell" => &mut theme.cell,
+                "cell_hover" => &mut theme.cell_hover,
+                "cell_pressed" => &mut theme.cell_pressed,
+                "highlight" => &mut theme.highlight,
+                "strike" => &mut theme.strike,
+                "text" => &mut theme.text,
+                "cross" => &mut theme.cross,
+                "circle" => &mut theme.circle,
+                _ => return Err(format!("line {number}: unknown key `{key}`")),
+            };
+            *colour = parse_colour(value).ok_or_else(|| format!("line {number}: invalid colour `{value}`, expected #RRGGBB"))?;
+        }
+        Ok(theme)
+    }
+
+    /// Load the theme file named by `THEME_VARIABLE` or, if that is not set,
+    /// `~/.config/rustic_tac_toe/theme` if it exists. Contains a message if the file cannot be
+    /// read or parsed.
+    #[must_use]
+    pub fn load_user_theme() -> Option<Result<Theme, String>> {
+        let path = match std::env::var_os(THEME_VARIABLE) {
+            Some(path) => PathBuf::from(path),
+            None => {
+                let config = std::env::var_os("XDG_CONFIG_HOME")
+                    .map(PathBuf::from)
+                    .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+                let path = config.join("rustic_tac_toe").join("theme");
+                if !path.exists() {
+                    return None;
+                }
+                path
+            }
+        };
+        Some(
+            std::fs::read_to_string(&path)
+                .map_err(|err| format!("{}: {err}", path.display()))
+                .and_then(|text| Theme::parse(&text).map_err(|err| format!("{}: {err}", path.display()))),
+        )
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+/// Parse a colour given as `#RRGGBB`.
+fn parse_colour(value: &str) -> Option<u32> {
+    let hex = value.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_overrides_base() {
+        let theme = Theme::parse("# my theme\nname = Mine\nbase = light\n\ncross = #123456\nstroke_width = 0.2\n").unwrap();
+        assert_eq!("Mine", theme.name);
+        assert_eq!(0x12_3456, theme.cross);
+        assert_eq!(Theme::light().circle, theme.circle);
+        assert!((theme.stroke_width - 0.2).abs() < f64::EPSILON);
+    }
+    #[test]
+    fn test_parse_base_with_space_in_name() {
+        let theme = Theme::parse("base = high_contrast").unwrap();
+        assert_eq!(Theme::high_contrast().cell_hover, theme.cell_hover);
+    }
+    #[test]
+    fn test_parse_rejects_invalid_colour() {
+        let err = Theme::parse("cell = #12345").unwrap_err();
+        assert!(err.starts_with("line 1:"));
+    }
+    #[test]
+    fn test_parse_rejects_unknown_key() {
+        assert!(Theme::parse("name = x\nbackground = #000000").unwrap_err().starts_with("line 2:"));
+    }
+}
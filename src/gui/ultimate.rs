@@ -0,0 +1,130 @@
+use fltk::{draw, enums::Color, group::Group, prelude::*};
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+use crate::tic_tac_toe::{Field, VariantMessage};
+use crate::tic_tac_toe::ultimate::{Ultimate, SIDE};
+use crate::tic_tac_toe::variant::Variant;
+use super::{BoardGrid, Theme, VariantSender, VariantView};
+
+/// The 81 cells of Ultimate tic-tac-toe as a 3x3 grid of `BoardGrid`s.
+///
+/// The boards the next move can be made on are framed, lines that won a small board are
+/// struck through and so is the line of boards that won the game.
+#[derive(Clone)]
+pub struct UltimateGrid {
+    group: Group,
+    boards: Vec<BoardGrid>,
+    theme: Rc<RefCell<Theme>>,
+    /// Whether each board is framed as playable.
+    active: Rc<RefCell<Vec<bool>>>,
+    /// First and last board of the line that won the game.
+    strike: Rc<RefCell<Option<(usize, usize)>>>,
+}
+
+impl UltimateGrid {
+    /// Place the boards in a square in the center of the given area with gaps between them.
+    pub fn layout(&mut self, x: i32, y: i32, w: i32, h: i32) {
+        let size = w.min(h);
+        let gap = size / 30;
+        let board_size = (size - 4 * gap) / 3;
+        let left = x + (w - size) / 2 + gap;
+        let top = y + (h - size) / 2 + gap;
+        for (index, board) in self.boards.iter_mut().enumerate() {
+            // like the cells, the first row of boards is at the bottom
+            let row = 2 - (index / 3) as i32;
+            let col = (index % 3) as i32;
+            board.resize(left + col * (board_size + gap), top + row * (board_size + gap), board_size, board_size);
+        }
+        self.group.redraw();
+    }
+
+    /// Frame the playable boards and strike through the line of boards that won the game.
+    fn draw_overlay(&self) {
+        let theme = self.theme.borrow();
+        let width = (self.boards[0].w() / 30).max(2);
+        draw::set_draw_color(Color::from_hex(theme.highlight));
+        draw::set_line_style(draw::LineStyle::Solid, width);
+        for (board, active) in self.boards.iter().zip(self.active.borrow().iter()) {
+            if *active {
+                draw::draw_rect(board.x() - width, board.y() - width, board.w() + 2 * width, board.h() + 2 * width);
+            }
+        }
+        if let Some((first, last)) = *self.strike.borrow() {
+            let center = |board: &BoardGrid| (board.x() + board.w() / 2, board.y() + board.h() / 2);
+            let (x1, y1) = center(&self.boards[first]);
+            let (x2, y2) = center(&self.boards[last]);
+            draw::set_draw_color(Color::from_hex(theme.strike));
+            draw::set_line_style(draw::LineStyle::Solid | draw::LineStyle::CapRound, width * 3);
+            draw::draw_line(x1, y1, x2, y2);
+        }
+        draw::set_line_style(draw::LineStyle::Solid, 0);
+    }
+}
+
+impl VariantView<Ultimate> for UltimateGrid {
+    fn new(x: i32, y: i32, w: i32, h: i32, _game: &Ultimate, s: VariantSender) -> UltimateGrid {
+        let group = Group::new(x, y, w, h, "");
+        let boards: Vec<BoardGrid> = (0..SIDE).map(|_| BoardGrid::new(x, y, w / 3, h / 3, 3, 3)).collect();
+        group.end();
+        let mut grid = UltimateGrid {
+            group,
+            boards,
+            theme: Rc::new(RefCell::new(Theme::default())),
+            active: Rc::new(RefCell::new(vec![true; SIDE])),
+            strike: Rc::new(RefCell::new(None)),
+        };
+        for (index, board) in grid.boards.iter_mut().enumerate() {
+            for (cell, but) in board.buttons_mut().iter_mut().enumerate() {
+                but.emit(s.sender(), s.message(VariantMessage::Number(index * SIDE + cell)));
+            }
+        }
+        grid.layout(x, y, w, h);
+        let mut resized_grid = grid.clone();
+        grid.group.resize_callback(move |_, x, y, w, h| resized_grid.layout(x, y, w, h));
+        let drawn_grid = grid.clone();
+        grid.group.draw(move |_| drawn_grid.draw_overlay());
+        grid
+    }
+
+    fn show(&mut self, game: &Ultimate, last: Option<usize>, ghost: Field) {
+        for (index, board) in self.boards.iter_mut().enumerate() {
+            board.show_board(game.board(index));
+            if let Some(line) = game.board_line(index) {
+                board.highlight_line(&line);
+            }
+            board.set_ghost(if game.is_playable(index) { ghost } else { Field::Free });
+        }
+        if let Some(mv) = last {
+            self.boards[mv / SIDE].set_mark(mv % SIDE, game.board(mv / SIDE)[mv % SIDE]);
+        }
+        *self.active.borrow_mut() = (0..SIDE).map(|index| game.is_playable(index)).collect();
+        *self.strike.borrow_mut() = game.winning_line().map(|line| (line[0], line[line.len() - 1]));
+        self.group.redraw();
+        if game.outcome().is_none() && game.history().is_empty() {
+            self.boards[4].focus(4);
+        }
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        *self.theme.borrow_mut() = theme.clone();
+        for board in &mut self.boards {
+            board.set_theme(theme);
+        }
+        self.group.redraw();
+    }
+}
+
+impl Deref for UltimateGrid {
+    type Target = Group;
+
+    fn deref(&self) -> &Self::Target {
+        &self.group
+    }
+}
+
+impl DerefMut for UltimateGrid {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.group
+    }
+}
@@ -0,0 +1,57 @@
+use fltk::{app, group::Group};
+use std::ops::DerefMut;
+use crate::tic_tac_toe::{Field, Message, VariantMessage};
+use crate::tic_tac_toe::variant::Variant;
+use super::Theme;
+
+/// Sends the messages of one variant window through the channel of the application, which
+/// all windows share, as `Message::Variant` with the id of the window.
+#[derive(Clone, Copy)]
+pub struct VariantSender {
+    window: usize,
+    sender: app::Sender<Message>,
+}
+
+impl VariantSender {
+    #[must_use]
+    pub fn new(window: usize, sender: app::Sender<Message>) -> VariantSender {
+        VariantSender { window, sender }
+    }
+
+    /// The channel of the application, to let widgets emit `message(…)`.
+    #[must_use]
+    pub fn sender(self) -> app::Sender<Message> {
+        self.sender
+    }
+
+    /// Address a message to the window.
+    #[must_use]
+    pub fn message(self, message: VariantMessage) -> Message {
+        Message::Variant(self.window, message)
+    }
+
+    pub fn send(self, message: VariantMessage) {
+        self.sender.send(self.message(message));
+    }
+}
+
+/// Board of a variant that is played in a window of its own.
+pub trait VariantView<G: Variant>: Clone + DerefMut<Target = Group> + 'static {
+    /// Width of the board divided by its height.
+    const ASPECT: f64 = 1.0;
+
+    /// Create the board for `game`. Its cells send `VariantMessage::Number` through `s`.
+    fn new(x: i32, y: i32, w: i32, h: i32, game: &G, s: VariantSender) -> Self;
+
+    /// Turn the click on a cell into a move. Views that need more than one click (or
+    /// other input) for a move return `None` until the move is complete.
+    fn click(&mut self, _game: &G, cell: usize) -> Option<usize> {
+        Some(cell)
+    }
+
+    /// Show the position of the game, animating the move `last`. `ghost` is previewed on the
+    /// cells the player to move can take, `Field::Free` for no preview.
+    fn show(&mut self, game: &G, last: Option<usize>, ghost: Field);
+
+    fn set_theme(&mut self, theme: &Theme);
+}
@@ -0,0 +1,112 @@
+use fltk::{button::RadioButton, enums::{Color, FrameType, Shortcut}, group::Group, prelude::*};
+use std::cell::Cell;
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+use crate::tic_tac_toe::{Field, VariantMessage};
+use crate::tic_tac_toe::wild::{move_mark, wild_move, Wild};
+use super::{BoardGrid, Theme, VariantSender, VariantView};
+
+/// The board of Wild tic-tac-toe with a selector below it for the mark the next move places,
+/// which can also be switched with the `X` and `O` keys.
+#[derive(Clone)]
+pub struct WildGrid {
+    group: Group,
+    grid: BoardGrid,
+    selectors: Vec<RadioButton>,
+    /// Mark placed by a click on the board.
+    mark: Rc<Cell<Field>>,
+    /// Whether the mark is previewed on the free cells.
+    ghost: Rc<Cell<bool>>,
+}
+
+impl WildGrid {
+    /// Place the board above the selector, which gets an eighth of the height.
+    pub fn layout(&mut self, x: i32, y: i32, w: i32, h: i32) {
+        let selector_h = h / 8;
+        let button_w = (w / 4).min(selector_h * 3);
+        let left = x + w / 2 - button_w;
+        self.grid.resize(x, y, w, h - selector_h);
+        for (index, selector) in self.selectors.iter_mut().enumerate() {
+            selector.resize(left + index as i32 * button_w, y + h - selector_h + selector_h / 8, button_w, selector_h * 3 / 4);
+        }
+        self.group.redraw();
+    }
+
+    /// Choose the mark the next click places.
+    fn select(&mut self, mark: Field) {
+        self.mark.set(mark);
+        if self.ghost.get() {
+            self.grid.set_ghost(mark);
+        }
+    }
+}
+
+impl VariantView<Wild> for WildGrid {
+    const ASPECT: f64 = 0.9;
+
+    fn new(x: i32, y: i32, w: i32, h: i32, _game: &Wild, s: VariantSender) -> WildGrid {
+        let group = Group::new(x, y, w, h, "");
+        let mut grid = BoardGrid::new(x, y, w, h, 3, 3);
+        let selectors: Vec<RadioButton> = ["Place X", "Place O"].iter().map(|label| RadioButton::new(x, y, w / 4, h / 8, *label)).collect();
+        group.end();
+        for (cell, but) in grid.buttons_mut().iter_mut().enumerate() {
+            but.emit(s.sender(), s.message(VariantMessage::Number(cell)));
+        }
+        let mut view = WildGrid { group, grid, selectors, mark: Rc::new(Cell::new(Field::Cross)), ghost: Rc::new(Cell::new(false)) };
+        for (index, (mark, key)) in [(Field::Cross, 'x'), (Field::Circle, 'o')].into_iter().enumerate() {
+            let mut selected_view = view.clone();
+            let selector = &mut view.selectors[index];
+            selector.set_frame(FrameType::GleamUpBox);
+            selector.set_shortcut(Shortcut::None | key);
+            selector.set_callback(move |_| selected_view.select(mark));
+        }
+        view.selectors[0].toggle(true);
+        view.layout(x, y, w, h);
+        let mut resized_view = view.clone();
+        view.group.resize_callback(move |_, x, y, w, h| resized_view.layout(x, y, w, h));
+        view
+    }
+
+    fn click(&mut self, _game: &Wild, cell: usize) -> Option<usize> {
+        Some(wild_move(cell, self.mark.get()))
+    }
+
+    /// `ghost` only tells whether the player to move places a mark, the preview shows the
+    /// selected one.
+    fn show(&mut self, game: &Wild, last: Option<usize>, ghost: Field) {
+        self.ghost.set(ghost != Field::Free);
+        self.grid.show_board(game.board());
+        self.grid.set_ghost(if ghost == Field::Free { Field::Free } else { self.mark.get() });
+        if let Some(mv) = last {
+            let (cell, mark) = move_mark(mv);
+            self.grid.set_mark(cell, mark);
+        }
+        if let Some(line) = game.winning_line() {
+            self.grid.highlight_line(&line);
+        }
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.grid.set_theme(theme);
+        for selector in &mut self.selectors {
+            selector.set_color(Color::from_hex(theme.cell));
+            selector.set_selection_color(Color::from_hex(theme.highlight));
+            selector.set_label_color(Color::from_hex(theme.text));
+        }
+        self.group.redraw();
+    }
+}
+
+impl Deref for WildGrid {
+    type Target = Group;
+
+    fn deref(&self) -> &Self::Target {
+        &self.group
+    }
+}
+
+impl DerefMut for WildGrid {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.group
+    }
+}
@@ -24,16 +24,283 @@ pub use crate::gui::*;
 pub mod tic_tac_toe {
 
     
-    #[derive(PartialEq)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
     pub enum Field {
         Cross,
         Circle,
         Free,
     }
+
+    /// Content of a cell, which is either free or taken: a mark, or a number in Numerical
+    /// tic-tac-toe.
+    pub trait CellContent: Copy + PartialEq {
+        /// Content of a free cell.
+        const FREE: Self;
+
+        fn is_free(self) -> bool {
+            self == Self::FREE
+        }
+    }
+
+    impl CellContent for Field {
+        const FREE: Field = Field::Free;
+    }
+
+    /// A number from 1 to 9 in a cell of Numerical tic-tac-toe, `None` for a free cell.
+    pub type Number = Option<u8>;
+
+    impl CellContent for Number {
+        const FREE: Number = None;
+    }
+
+    /// Who plays the crosses and the circles.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Mode {
+        /// The player has the crosses, the AI the circles.
+        VersusAi,
+        /// Two players take turns at the same computer.
+        Hotseat,
+        /// The AI plays both sides.
+        AiVersusAi,
+        /// Marks are placed freely to set up a position that the solver analyses.
+        Analysis,
+    }
+
     #[derive(Clone, Copy, Debug)]
     pub enum Message {
         Number(usize),
-        NewGame
+        NewGame,
+        Undo,
+        Hint,
+        NextTheme,
+        /// Let the AI make the next move.
+        AiTurn,
+        /// The AI chose a cell in answer to the request with the given number.
+        AiMove(u64, usize),
+        /// The think time for the request with the given number is over.
+        AiTimeout(u64),
+        Save,
+        Load,
+        Quit,
+        SetMode(Mode),
+        SetDifficulty(i32),
+        SetTheme(usize),
+        SetBoard(Dimensions),
+        /// Choose between the standard (`false`) and the misère rules (`true`).
+        SetMisere(bool),
+        ToggleGravity,
+        ToggleLimited,
+        /// Show the position after the given number of moves without changing the game.
+        ShowPosition(usize),
+        /// Choose the difficulty of the AI playing the given side when it plays both sides.
+        SetStrategy(Field, i32),
+        /// Set the speed of the AI playing both sides in moves per second.
+        SetSpeed(f64),
+        TogglePause,
+        /// Let the paused AI make a single move.
+        Step,
+        /// Set the time the AI may think about a move in seconds.
+        SetThinkTime(f64),
+        /// Scale the whole GUI by the given factor.
+        SetZoom(f32),
+        /// Open a window with a game of the given variant.
+        OpenVariant(variant::VariantKind),
+        /// Message for the variant window with the given id.
+        Variant(usize, VariantMessage),
+    }
+
+    /// Message of a variant window. All windows share the channel of the application, so
+    /// these are sent as `Message::Variant` together with the id of their window.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum VariantMessage {
+        Number(usize),
+        NewGame,
+        Undo,
+        /// Close the window.
+        Close,
+        SetMode(Mode),
+        /// Play against the AI, which takes the given side.
+        SetAiSide(Field),
+        SetDifficulty(i32),
+        /// The AI chose a move in answer to the request with the given number.
+        AiMove(u64, usize),
+        /// The think time for the request with the given number is over.
+        AiTimeout(u64),
+    }
+
+    /// Rule options that change how the game on a board is decided.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct Rules {
+        /// Completing a line loses instead of winning.
+        pub misere: bool,
+        /// Marks fall to the lowest free cell of their column.
+        pub gravity: bool,
+        /// Every player keeps only as many marks as a line needs; placing another one removes
+        /// their oldest mark, so the board never fills up. Cannot be combined with gravity,
+        /// which would leave marks floating.
+        pub limited: bool,
+    }
+
+    impl Rules {
+        /// Rules of the misère variant.
+        #[must_use]
+        pub fn misere() -> Rules {
+            Rules { misere: true, ..Rules::default() }
+        }
+
+        /// Rules where marks are dropped into the columns.
+        #[must_use]
+        pub fn gravity() -> Rules {
+            Rules { gravity: true, ..Rules::default() }
+        }
+
+        /// Rules where the oldest mark of a player vanishes.
+        #[must_use]
+        pub fn limited() -> Rules {
+            Rules { limited: true, ..Rules::default() }
+        }
+    }
+
+    /// Direction of a line on the board.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Direction {
+        Row,
+        Column,
+        /// From the bottom left to the top right.
+        Diagonal,
+        /// From the bottom right to the top left.
+        AntiDiagonal,
+    }
+
+    /// Size of an m,n,k board: `rows` x `columns` cells and `in_a_row` marks in a line to win.
+    ///
+    /// Cells are stored row by row, so cell `i` is in row `i / columns` and column `i % columns`.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct Dimensions {
+        pub rows: usize,
+        pub columns: usize,
+        pub in_a_row: usize,
+    }
+
+    impl Dimensions {
+        /// # Panics
+        ///
+        /// Panics if no line of `in_a_row` cells fits on the board.
+        #[must_use]
+        pub fn new(rows: usize, columns: usize, in_a_row: usize) -> Dimensions {
+            assert!(in_a_row > 0 && in_a_row <= rows.max(columns), "{in_a_row} in a row do not fit on a {rows}x{columns} board");
+            Dimensions { rows, columns, in_a_row }
+        }
+
+        /// Number of cells on the board.
+        #[must_use]
+        pub fn cells(&self) -> usize {
+            self.rows * self.columns
+        }
+
+        /// Index of the center cell (the lower right one of the central cells for even sizes).
+        #[must_use]
+        pub fn center(&self) -> usize {
+            (self.rows / 2) * self.columns + self.columns / 2
+        }
+
+        /// Name of a cell as column letter and row number, counted from the bottom left like
+        /// on a numeric keypad (cell 0 is `a1`).
+        #[must_use]
+        pub fn cell_name(&self, index: usize) -> String {
+            let column = char::from(b'a' + (index % self.columns % 26) as u8);
+            format!("{column}{}", index / self.columns + 1)
+        }
+
+        /// Return the cell with the given name as written by `cell_name`.
+        #[must_use]
+        pub fn cell_index(&self, name: &str) -> Option<usize> {
+            let mut chars = name.chars();
+            let column = (chars.next()? as usize).checked_sub('a' as usize)?;
+            let row = chars.as_str().parse::<usize>().ok()?.checked_sub(1)?;
+            if row < self.rows && column < self.columns {
+                Some(row * self.columns + column)
+            }
+            else {
+                None
+            }
+        }
+
+        /// Return the cell `d_row` rows and `d_col` columns away from `index`, if it is on the
+        /// board.
+        #[must_use]
+        pub fn neighbour(&self, index: usize, d_row: isize, d_col: isize) -> Option<usize> {
+            let row = (index / self.columns).checked_add_signed(d_row)?;
+            let col = (index % self.columns).checked_add_signed(d_col)?;
+            if row < self.rows && col < self.columns {
+                Some(row * self.columns + col)
+            }
+            else {
+                None
+            }
+        }
+
+        /// Return the cells that are connected to `index` on a board drawn with lines between
+        /// the cells, like the board of Three Men's Morris: the orthogonal neighbours and, on a
+        /// square board, the neighbours along its two long diagonals. Diagonal neighbours come
+        /// first, then the cells below, above, right and left.
+        #[must_use]
+        pub fn adjacent(&self, index: usize) -> Vec<usize> {
+            let (row, col) = (index / self.columns, index % self.columns);
+            let last = self.rows - 1;
+            let on_diagonal = self.rows == self.columns && row == col;
+            let on_anti_diagonal = self.rows == self.columns && row + col == last;
+            let mut steps = vec![];
+            if on_anti_diagonal {
+                steps.extend([(-1, 1), (1, -1)]);
+            }
+            if on_diagonal {
+                steps.extend([(-1, -1), (1, 1)]);
+            }
+            steps.extend([(1, 0), (-1, 0), (0, 1), (0, -1)]);
+            steps.into_iter().filter_map(|(d_row, d_col)| self.neighbour(index, d_row, d_col)).collect()
+        }
+
+        /// Return all lines of `in_a_row` cells in the given direction.
+        #[must_use]
+        pub fn lines_in(&self, direction: Direction) -> Vec<Vec<usize>> {
+            let (d_row, d_col) = match direction {
+                Direction::Row => (0, 1),
+                Direction::Column => (1, 0),
+                Direction::Diagonal => (1, 1),
+                Direction::AntiDiagonal => (1, -1),
+            };
+            let mut lines = vec![];
+            for start in 0..self.cells() {
+                let mut line = vec![start];
+                while line.len() < self.in_a_row {
+                    match self.neighbour(line[line.len() - 1], d_row, d_col) {
+                        Some(next) => line.push(next),
+                        None => break,
+                    }
+                }
+                if line.len() == self.in_a_row {
+                    lines.push(line);
+                }
+            }
+            lines
+        }
+
+        /// Return all lines of `in_a_row` cells on the board.
+        #[must_use]
+        pub fn lines(&self) -> Vec<Vec<usize>> {
+            [Direction::Row, Direction::Column, Direction::Diagonal, Direction::AntiDiagonal]
+                .iter()
+                .flat_map(|direction| self.lines_in(*direction))
+                .collect()
+        }
+    }
+
+    impl Default for Dimensions {
+        /// The classic 3x3 board with three in a row.
+        fn default() -> Self {
+            Dimensions::new(3, 3, 3)
+        }
     }
 
     /// This module contains all checks performed in the game, including:
@@ -42,17 +309,13 @@ pub mod tic_tac_toe {
     /// - check if the game ended in a stale mate
     /// - checks if the player is close to winning (for the AI to prevent it)
     pub mod check_functions {
-        use super::Field;
-        /// Check if we have three crosses or circels in a row, column or diagonal
-        /// Which row, column or diagonal is checked is controlled by the start index and the step
-        /// size.
-        fn check_for_three(board: &[Field], index: usize, step_size: usize) -> (bool, Field) {
-            if board[index] == board[index+2*step_size] && board[index+step_size] == board[index+2*step_size] {
-                 match board[index] {
-                    Field::Cross => (true, Field::Cross),
-                    Field::Circle => (true, Field::Circle),
-                    Field::Free => (false, Field::Free),
-                }
+        use super::{CellContent, Dimensions, Direction, Field, Number, Rules};
+        use std::sync::OnceLock;
+        /// Check if we have a full line of crosses or circles in a row, column or diagonal.
+        fn check_for_line(board: &[Field], line: &[usize]) -> (bool, Field) {
+            let first = board[line[0]];
+            if first != Field::Free && line.iter().all(|index| board[*index] == first) {
+                (true, first)
             }
             else {
                 (false, Field::Free)
@@ -61,97 +324,176 @@ pub mod tic_tac_toe {
         
         /// Check if game is over
         #[must_use]
-        pub fn someone_has_won(board: &[Field]) -> (bool, Field) {
-            for i in [0, 3, 6] {
-                let return_value = check_for_three(board, i, 1);
-                if return_value.0 {
-                    return return_value
-                }
+        pub fn someone_has_won(board: &[Field], dims: &Dimensions) -> (bool, Field) {
+            match winning_line(board, dims) {
+                Some(line) => (true, board[line[0]]),
+                None => (false, Field::Free),
             }
-            for i in [0, 1, 2] {
-                let return_value = check_for_three(board, i, 3);
-                if return_value.0 {
-                    return return_value
-                }
+        }
+
+        /// Return the winner of the game under `rules`: the player who completed a line or,
+        /// in misère, the opponent of that player.
+        #[must_use]
+        pub fn winner(board: &[Field], dims: &Dimensions, rules: &Rules) -> Option<Field> {
+            let (completed, player) = someone_has_won(board, dims);
+            match (completed, rules.misere, player) {
+                (false, _, _) => None,
+                (true, false, player) => Some(player),
+                (true, true, Field::Cross) => Some(Field::Circle),
+                (true, true, _) => Some(Field::Cross),
             }
-            let return_value = check_for_three(board, 0, 4);
-            if return_value.0 {
-                return return_value
+        }
+
+        /// Return the lowest free cell of a column, where a dropped mark lands.
+        #[must_use]
+        pub fn drop_cell(board: &[Field], dims: &Dimensions, column: usize) -> Option<usize> {
+            (0..dims.rows).map(|row| row * dims.columns + column).find(|cell| board[*cell] == Field::Free)
+        }
+
+        /// Return the cells a mark may be placed in: every free cell or, with gravity, the
+        /// lowest free cell of every column.
+        #[must_use]
+        pub fn available_moves(board: &[Field], dims: &Dimensions, rules: &Rules) -> Vec<usize> {
+            if rules.gravity {
+                (0..dims.columns).filter_map(|column| drop_cell(board, dims, column)).collect()
             }
-            let return_value = check_for_three(board, 2, 2);
-            if return_value.0 {
-                return return_value
+            else {
+                (0..board.len()).filter(|cell| board[*cell] == Field::Free).collect()
             }
-            (false, Field::Free)
+        }
+
+        /// Return the lines of the 3x3 board, which are computed only once for the variants
+        /// that check them all the time.
+        #[must_use]
+        pub fn lines_3x3() -> &'static [Vec<usize>] {
+            static LINES: OnceLock<Vec<Vec<usize>>> = OnceLock::new();
+            LINES.get_or_init(|| Dimensions::default().lines())
+        }
+
+        /// Return the cells of the first completed line, if there is one.
+        #[must_use]
+        pub fn winning_line(board: &[Field], dims: &Dimensions) -> Option<Vec<usize>> {
+            dims.lines().into_iter().find(|line| check_for_line(board, line).0)
+        }
+
+        /// Return the cells of the first full line of numbers that add up to `total`, the
+        /// counterpart of `winning_line` for Numerical tic-tac-toe.
+        #[must_use]
+        pub fn line_with_sum(board: &[Number], dims: &Dimensions, total: u8) -> Option<Vec<usize>> {
+            dims.lines().into_iter().find(|line| {
+                let numbers: Option<Vec<u8>> = line.iter().map(|index| board[*index]).collect();
+                numbers.is_some_and(|numbers| numbers.iter().sum::<u8>() == total)
+            })
         }
 
         /// Check for remis.
         #[must_use]
-        pub fn cats_game(board: &[Field]) -> bool {
+        pub fn cats_game<C: CellContent>(board: &[C]) -> bool {
             // for item in &(reference) iterator
             for i in board {
-                if *i == Field::Free {
+                if i.is_free() {
                     return false
                 }
             }
             true
         }
 
-        /// Check if player has two out of three already and return index of missing value to
-        /// prevent player win.
-        #[must_use]
-        pub fn check_for_two(board: &[Field], col: bool) -> (bool, usize) {
-            for i in 0..3 {
+        /// Return the missing cell of the first line in which one player has all but one cell
+        /// and the last cell is still free.
+        fn check_for_threat(board: &[Field], lines: &[Vec<usize>]) -> (bool, usize) {
+            for line in lines {
+                let mut owner = Field::Free;
                 let mut count = 0;
-                let mut free_cell_index = 0;
-                for j in 0..3 {
-                    let index = if col {
-                         i + 3*j
+                let mut free_cell_index = None;
+                for index in line {
+                    match board[*index] {
+                        Field::Free => free_cell_index = Some(*index),
+                        mark if owner == Field::Free || owner == mark => {
+                            owner = mark;
+                            count += 1;
+                        }
+                        _ => break,
+                    }
+                }
+                if let Some(free_cell_index) = free_cell_index {
+                    if count + 1 == line.len() {
+                        return (true, free_cell_index)
                     }
-                    else { j + 3*i};
-                    if board[index] == Field::Free { free_cell_index = index; }
-                    else { count += 1; }
                 }
-                if count == 2 { return (true, free_cell_index) }
-
             }
             (false, 0)
         }
 
+        /// Check if player has all but one of a row (or column if `col` is set) already and
+        /// return index of missing value to prevent player win.
         #[must_use]
-        pub fn check_for_diagonals(board: &[Field]) -> (bool, usize)  {
-            for i in [[2, 4, 6], [0, 4, 8]] {
-                let mut diagonal_count = 0;
-                let mut free_cell = 0;
-                for j in i {
-                    if board[j] == Field::Free {
-                        free_cell = j;
-                    }
-                    else {
-                        diagonal_count += 1;
-                    }
-                }
-                if diagonal_count == 2 {
-                    return (true, free_cell)
-                }
-            }
-            (false, 0)
+        pub fn check_for_two(board: &[Field], dims: &Dimensions, col: bool) -> (bool, usize) {
+            let direction = if col { Direction::Column } else { Direction::Row };
+            check_for_threat(board, &dims.lines_in(direction))
+        }
+
+        /// Same as `check_for_two` for both diagonal directions.
+        #[must_use]
+        pub fn check_for_diagonals(board: &[Field], dims: &Dimensions) -> (bool, usize)  {
+            let mut lines = dims.lines_in(Direction::AntiDiagonal);
+            lines.extend(dims.lines_in(Direction::Diagonal));
+            check_for_threat(board, &lines)
         }
     }
 
 
+    /// Rotations and reflections of the board and its canonical form.
+    pub mod symmetry;
+
+    /// Perfect-play solver and the transposition table it uses.
+    pub mod solver;
+
+    /// Bitboard representation of the board with precomputed line masks.
+    pub mod bitboard;
+
+    /// State of a running game and the score of a session.
+    pub mod session;
+
+    /// Interface and search of the variants that are played in a window of their own.
+    pub mod variant;
+
+    /// Ultimate tic-tac-toe, a 3x3 grid of 3x3 boards.
+    pub mod ultimate;
+
+    /// Qubic, four in a row on a 4x4x4 cube.
+    pub mod qubic;
+
+    /// Gomoku, five in a row on a 15x15 board.
+    pub mod gomoku;
+
+    /// Search for the rules where the oldest mark of a player vanishes.
+    pub mod limited;
+
+    /// Three Men's Morris, where the marks are moved once they are all placed.
+    pub mod morris;
+
+    /// Wild tic-tac-toe, where every move places a cross or a circle.
+    pub mod wild;
+
+    /// Notakto, where both players place crosses on several boards and the last line loses.
+    pub mod notakto;
+
+    /// Numerical tic-tac-toe, where the players place numbers and a line adding up to 15 wins.
+    pub mod numerical;
+
     /// This module contains the entire AI logic.
     mod ai_functions {
-        use super::Field;
+        use super::{Dimensions, Field, Rules};
+        use super::solver::opponent;
         extern crate rand;
         use rand::Rng;
         use rand::distributions::Uniform;
         use super::check_functions::check_for_two;
         use super::check_functions::check_for_diagonals;       
-        use fltk::prelude::WidgetExt;
-        /// Determine the next move of the AI - either a good calculated move or a random
+        use super::check_functions::available_moves;
+        /// Determine the next move of `player` - either a good calculated move or a random
         /// placement.
-        pub fn ai_move(board: &mut Vec<Field>, button_vectors: &mut Vec<&mut crate::gui::MyButton>, difficulty: i32) {
+        pub fn choose_move(board: &[Field], dims: &Dimensions, rules: &Rules, player: Field, difficulty: i32) -> usize {
             let mut random: bool = false;
             let mut rng = rand::thread_rng();
             if difficulty == 0 {
@@ -173,102 +515,232 @@ pub mod tic_tac_toe {
             }
             if random {
                 // random for the way
-                let mut num: usize = rng.gen_range(0..9);
-                while board[num] == Field::Cross || board[num] == Field::Circle {
-                    num = rng.gen_range(0..9);
-                }
-                board[num] = Field::Circle;
-                button_vectors[num].set_label("O");
+                let moves = available_moves(board, dims, rules);
+                moves[rng.gen_range(0..moves.len())]
             }
             else {
-                // Do a good move. Look for a spot thas has an x adjacent to it. Prioritize the middle.
-                if board[4] != Field::Cross && board[4] != Field::Circle {
-                    board[4] = Field::Circle;
-                    button_vectors[4].set_label("O");
+                heuristic_move(board, dims, rules, player)
+            }
+        }
 
+        /// Do a good move. Look for a spot thas has a mark of `player` adjacent to it.
+        /// Prioritize the middle.
+        pub fn heuristic_move(board: &[Field], dims: &Dimensions, rules: &Rules, player: Field) -> usize {
+            if rules.misere {
+                return misere_move(board, dims, rules, player);
+            }
+            if rules.gravity {
+                return gravity_move(board, dims, player);
+            }
+            let center = dims.center();
+            if board[center] != Field::Cross && board[center] != Field::Circle {
+                return center;
+            }
+            // check if there are any lines that are only one mark short
+            let two_in_rows = check_for_two(board, dims, false);
+            if two_in_rows.0 {
+                return two_in_rows.1
+            }
+            let two_in_cols = check_for_two(board, dims, true);
+            if two_in_cols.0 {
+                return two_in_cols.1
+            }
+            let two_in_diags = check_for_diagonals(board, dims);
+            if two_in_diags.0 {
+                return two_in_diags.1
+            }
+            let mut empty_spot = board.len();
+            for i in 0..board.len() {
+                if board[i] == player {
+                    if let Some(new_spot) = get_available_adjacent(board, dims, i) {
+                        return new_spot;
+                    }
                 }
-                else {
-                    // check if there are any 2Xs in a row
-                    let two_in_rows = check_for_two(board, false);
-                    if two_in_rows.0 {
-                        board[two_in_rows.1] = Field::Circle;
-                        button_vectors[two_in_rows.1].set_label("O");
-                        return
-                    }
-                    let two_in_cols = check_for_two(board, true);
-                    if two_in_cols.0 {
-                        board[two_in_cols.1] = Field::Circle;
-                        button_vectors[two_in_cols.1].set_label("O");
-                        return
-                    }
-                    let two_in_diags = check_for_diagonals(board);
-                    if two_in_diags.0 {
-                        board[two_in_diags.1] = Field::Circle;
-                        button_vectors[two_in_diags.1].set_label("O");
-                        return
-                    }
-                    let mut empty_spot = 10;
-                    for i in 0..9 {
-                        if board[i] == Field::Circle {
-                            let new_spot = get_available_adjacent(board, i);
-                            if new_spot < board.len() {
-                                board[new_spot] = Field::Circle;
-                                button_vectors[new_spot].set_label("O");
-                                return;
-                            }
-                        }
-                        else if board[i] != Field::Cross {
-                            empty_spot = i;
-                        }
+                else if board[i] == Field::Free {
+                    empty_spot = i;
+                }
+            }
+            empty_spot
+        }
+
+        /// Do a good move when completing a line loses. Never complete a line of `player` while
+        /// another cell is free and prefer cells in few lines with marks of `player`, leaving
+        /// the opponent as few safe cells as possible.
+        ///
+        /// On boards with a single center, the player holding the center answers every move
+        /// with the cell opposite to it, which never completes a line first.
+        fn misere_move(board: &[Field], dims: &Dimensions, rules: &Rules, player: Field) -> usize {
+            let lines = dims.lines();
+            let other = opponent(player);
+            let completes = |board: &[Field], cell: usize, player: Field| {
+                lines.iter().any(|line| line.contains(&cell) && line.iter().all(|index| *index == cell || board[*index] == player))
+            };
+            let free = available_moves(board, dims, rules);
+            // with gravity the opposite cell is rarely available, so the strategy does not apply
+            let single_center = dims.rows % 2 == 1 && dims.columns % 2 == 1 && !rules.gravity;
+            if single_center && free.len() == board.len() {
+                return dims.center();
+            }
+            if single_center && board[dims.center()] == player {
+                for cell in 0..board.len() {
+                    let mirror = board.len() - 1 - cell;
+                    if board[cell] == other && board[mirror] == Field::Free && !completes(board, mirror, player) {
+                        return mirror;
                     }
-                    board[empty_spot] = Field::Circle;
-                    button_vectors[empty_spot].set_label("O");
+                }
+            }
+            let mut best: Option<((usize, usize, usize), usize)> = None;
+            for cell in &free {
+                // cells of own lines that are still open count as danger
+                let danger: usize = lines
+                    .iter()
+                    .filter(|line| line.contains(cell) && line.iter().all(|index| board[*index] != other))
+                    .map(|line| line.iter().filter(|index| board[**index] == player).count())
+                    .sum();
+                let mut next = board.to_vec();
+                next[*cell] = player;
+                let safe_for_opponent = available_moves(&next, dims, rules).into_iter().filter(|other_cell| !completes(&next, *other_cell, other)).count();
+                let key = (usize::from(completes(board, *cell, player)), danger, safe_for_opponent);
+                if best.is_none_or(|(best_key, _)| key < best_key) {
+                    best = Some((key, *cell));
+                }
+            }
+            best.map_or(board.len(), |(_, cell)| cell)
+        }
 
+        /// Do a good move when marks fall down the columns. Complete a line, block the
+        /// opponent's line and never drop a mark right below a cell that completes a line of
+        /// the opponent. Otherwise prefer cells in many open lines, and central columns.
+        fn gravity_move(board: &[Field], dims: &Dimensions, player: Field) -> usize {
+            let lines = dims.lines();
+            let other = opponent(player);
+            let completes = |cell: usize, player: Field| {
+                lines.iter().any(|line| line.contains(&cell) && line.iter().all(|index| *index == cell || board[*index] == player))
+            };
+            let moves = available_moves(board, dims, &Rules::gravity());
+            for who in [player, other] {
+                if let Some(cell) = moves.iter().find(|cell| completes(**cell, who)) {
+                    return *cell;
+                }
+            }
+            let mut best: Option<((bool, usize, usize), usize)> = None;
+            for cell in &moves {
+                let opens_win = dims.neighbour(*cell, 1, 0).is_some_and(|above| completes(above, other));
+                // lines with marks of a single player count for attack and defence
+                let value: usize = lines
+                    .iter()
+                    .filter(|line| line.contains(cell))
+                    .map(|line| {
+                        let own = line.iter().filter(|index| board[**index] == player).count();
+                        let theirs = line.iter().filter(|index| board[**index] == other).count();
+                        match (own, theirs) {
+                            (marks, 0) | (0, marks) => (marks + 1) * (marks + 1),
+                            _ => 0,
+                        }
+                    })
+                    .sum();
+                let off_center = (cell % dims.columns).abs_diff(dims.columns / 2);
+                let key = (!opens_win, value, dims.columns - off_center);
+                if best.is_none_or(|(best_key, _)| key > best_key) {
+                    best = Some((key, *cell));
                 }
             }
+            best.map_or(board.len(), |(_, cell)| cell)
         }
 
         /// Check which spot to choose for AI move if player is not close to winning.
-        fn get_available_adjacent(board: &[Field], spot: usize) -> usize {
-            if spot == 4 {
-                let next_spots = vec![2, 0, 6, 8];
-                for field in &next_spots {
-                    if board[*field] == Field::Free {
-                        return next_spots[*field];
-                    }
+        ///
+        /// Returns the first free cell connected to `spot` (see `Dimensions::adjacent`), which
+        /// prefers the diagonals, or `None` if all of them are taken.
+        fn get_available_adjacent(board: &[Field], dims: &Dimensions, spot: usize) -> Option<usize> {
+            dims.adjacent(spot).into_iter().find(|next_spot| board[*next_spot] == Field::Free)
+        }
+
+        #[cfg(test)]
+        mod tests {
+            use super::*;
+            use super::super::check_functions::{cats_game, drop_cell, someone_has_won};
+            use std::collections::HashSet;
+
+            /// Check the misère move of every position that `player` can reach from `board`
+            /// without a line on it.
+            fn check_misere_moves(board: &mut Vec<Field>, dims: &Dimensions, player: Field, seen: &mut HashSet<Vec<Field>>) {
+                if someone_has_won(board, dims).0 || cats_game(board) || !seen.insert(board.clone()) {
+                    return;
+                }
+                let completes = |board: &[Field], cell: usize| {
+                    let mut next = board.to_vec();
+                    next[cell] = player;
+                    someone_has_won(&next, dims).0
+                };
+                let free = available_moves(board, dims, &Rules::misere());
+                let cell = misere_move(board, dims, &Rules::misere(), player);
+                assert!(free.contains(&cell));
+                assert!(!completes(board, cell) || free.iter().all(|other| completes(board, *other)), "{cell} completes a line on {board:?}");
+                for cell in free {
+                    board[cell] = player;
+                    check_misere_moves(board, dims, opponent(player), seen);
+                    board[cell] = Field::Free;
                 }
             }
-            if spot + 3 < board.len() && board[spot + 3] != Field::Cross && board[spot + 3] != Field::Circle {
-                return spot + 3;
-            }
-            else if (spot - 2) > 0 && board[spot - 3] != Field::Cross && board[spot - 3] != Field::Circle {
-                return spot - 3;
+            #[test]
+            fn test_misere_move_avoids_lines() {
+                check_misere_moves(&mut vec![Field::Free; 9], &Dimensions::default(), Field::Cross, &mut HashSet::new());
             }
-            else if ((spot + 1) / 3) == (spot / 3) && spot + 1 < board.len() {
-                if board[spot + 1] != Field::Cross && board[spot + 1] != Field::Circle {
-                    return spot + 1;
+            #[test]
+            fn test_gravity_move_drops_marks() {
+                for dims in [Dimensions::default(), Dimensions::new(6, 7, 4)] {
+                    let mut board = vec![Field::Free; dims.rows * dims.columns];
+                    let mut player = Field::Cross;
+                    while !someone_has_won(&board, &dims).0 && !cats_game(&board) {
+                        let cell = gravity_move(&board, &dims, player);
+                        assert!((0..dims.columns).any(|column| drop_cell(&board, &dims, column) == Some(cell)), "{cell} is not the lowest free cell of its column");
+                        board[cell] = player;
+                        player = opponent(player);
+                    }
                 }
             }
-            else if ((spot -1) / 3) == (spot / 3) && board[spot - 1] != Field::Cross && board[spot - 1] != Field::Circle {
-                    return spot - 1;
+            #[test]
+            fn test_gravity_move_blocks_column() {
+                let dims = Dimensions::new(6, 7, 4);
+                let mut board = vec![Field::Free; 42];
+                for cell in [0, 7, 14] {
+                    board[cell] = Field::Circle;
                 }
-            for (i, cell) in board.iter().enumerate() {
-                if *cell != Field::Cross && *cell != Field::Circle {
-                    return i;
+                for cell in [1, 2, 8] {
+                    board[cell] = Field::Cross;
                 }
+                assert_eq!(21, gravity_move(&board, &dims, Field::Cross));
             }
-            10
         }
     }
 
     /// This module contains the main loop of the game and player interaction.
     pub mod game {
-        use super::{Field, Message, check_functions};
-        use super::check_functions::{cats_game, someone_has_won};
-        use super::ai_functions::ai_move;
+        use super::{Dimensions, Field, Message, Mode, Rules};
+        use super::ai_functions::{choose_move, heuristic_move};
+        use super::check_functions::{cats_game, drop_cell, winner, winning_line};
+        use super::session::{validate_position, Game, IllegalMove, Outcome, Score};
+        use super::solver::{opponent, Solver};
+        use super::ultimate::Ultimate;
+        use super::qubic::Qubic;
+        use super::gomoku::{Gomoku, GomokuRule};
+        use super::limited;
+        use super::morris::Morris;
+        use super::wild::Wild;
+        use super::notakto::Notakto;
+        use super::numerical::Numerical;
+        use super::variant::{VariantKind, WindowList};
+        use variant_window::{VariantTable, VariantWindow};
         use fltk::app;
         use fltk::dialog;
+        use fltk::{enums::{Color, Cursor, Event, Key, Shortcut}, menu::{MenuBar, MenuFlag}, prelude::*, window::Window};
+        use std::cmp::Ordering;
+        use std::thread;
+        use std::time::Duration;
         pub use crate::gui::*;
+        /// Window and controller of the variants that are played in a window of their own.
+        mod variant_window;
         /// Print board to command line
         fn _print_board(board: &[Field]) {
             println!("-------------");
@@ -290,160 +762,1084 @@ pub mod tic_tac_toe {
             println!("-------------");
         }
 
-        /// Control game loop and in-game player interaction.
-        fn game_loop() -> i32 {
-            use fltk::{enums::Color, group::{Pack, PackType}, prelude::*,window::Window,};            
-            // create gui
-            let app = app::App::default().with_scheme(app::Scheme::Gleam);
-            let win_w = 600;
-            let win_h = 600;
+        /// Names of the difficulties in the order of their numbers.
+        const DIFFICULTIES: [&str; 3] = ["Easy", "Challenge", "Hard"];
+
+        /// Game modes with their names in the menu.
+        const MODES: [(&str, Mode); 4] = [
+            ("Versus AI", Mode::VersusAi),
+            ("Hotseat", Mode::Hotseat),
+            ("AI versus AI", Mode::AiVersusAi),
+            ("Analysis board", Mode::Analysis),
+        ];
+
+        /// Height of the menu bar in pixels.
+        const MENU_HEIGHT: i32 = 25;
+
+        /// Moves per second of the AI playing both sides until the speed is changed.
+        const DEFAULT_WATCH_SPEED: f64 = 2.0;
+
+        /// Number of moves the AI playing both sides waits before it starts the next game.
+        const MOVES_BETWEEN_GAMES: f64 = 3.0;
+
+        /// Think times of the AI that can be chosen in the menu, in seconds.
+        const THINK_TIMES: [(&str, f64); 3] = [("1 second", 1.0), ("3 seconds", 3.0), ("10 seconds", 10.0)];
+
+        /// Index of the think time in `THINK_TIMES` used until another one is chosen.
+        const DEFAULT_THINK_TIME: usize = 1;
+
+        /// Zoom factors of the GUI that can be chosen in the menu. `ZOOM_VARIABLE` sets the
+        /// zoom at the start.
+        const ZOOMS: [(&str, f32); 5] = [("75%", 0.75), ("100%", 1.0), ("125%", 1.25), ("150%", 1.5), ("200%", 2.0)];
+
+        /// Milliseconds the search may take for a hint with limited marks.
+        const HINT_TIME: u64 = 200;
+
+        /// Board sizes that can be chosen in the menu.
+        fn board_sizes() -> [(&'static str, Dimensions); 4] {
+            [
+                ("3x3", Dimensions::default()),
+                ("4x4", Dimensions::new(4, 4, 4)),
+                ("5x5 (4 in a row)", Dimensions::new(5, 5, 4)),
+                ("7x6 (4 in a row)", Dimensions::new(6, 7, 4)),
+            ]
+        }
+
+        /// Variants that can be opened from the menu.
+        const VARIANTS: [(&str, VariantKind); 12] = [
+            ("Ultimate tic-tac-toe", VariantKind::Ultimate),
+            ("Qubic (4x4x4)", VariantKind::Qubic),
+            ("Gomoku/Freestyle", VariantKind::Gomoku(GomokuRule::Freestyle)),
+            ("Gomoku/Exactly five", VariantKind::Gomoku(GomokuRule::ExactFive)),
+            ("Three Men's Morris", VariantKind::Morris),
+            ("Wild tic-tac-toe", VariantKind::Wild),
+            ("Notakto/1 board", VariantKind::Notakto(1)),
+            ("Notakto/2 boards", VariantKind::Notakto(2)),
+            ("Notakto/3 boards", VariantKind::Notakto(3)),
+            ("Notakto/4 boards", VariantKind::Notakto(4)),
+            ("Notakto/5 boards", VariantKind::Notakto(5)),
+            ("Numerical tic-tac-toe", VariantKind::Numerical),
+        ];
+
+        /// Rules that can be chosen in the menu, by whether they are misère. Gravity can be
+        /// added to both.
+        const RULES: [(&str, bool); 2] = [("Standard", false), ("Misère (completing a line loses)", true)];
+
+        /// Escape the characters FLTK treats specially in menu labels.
+        fn menu_label(text: &str) -> String {
+            text.replace('\\', "\\\\").replace('/', "\\/").replace('&', "&&").replace('_', "\\_")
+        }
+
+        /// Check the menu item at `items[index]` and uncheck the other items of its radio group.
+        fn check_radio_item(menu: &MenuBar, items: &[i32], index: usize) {
+            for (i, item) in items.iter().enumerate() {
+                if let Some(mut item) = menu.at(*item) {
+                    if i == index {
+                        item.set();
+                    }
+                    else {
+                        item.clear();
+                    }
+                }
+            }
+        }
+
+        /// Widgets of the main window and the themes that can be applied to them.
+        struct Gui {
+            wind: Window,
+            menu: MenuBar,
+            grid: BoardGrid,
+            moves: MoveList,
+            watch: WatchPanel,
+            status: StatusPanel,
+            themes: Vec<Theme>,
+            theme: usize,
+            /// Indices of the radio items in the menu.
+            mode_items: Vec<i32>,
+            difficulty_items: Vec<i32>,
+            theme_items: Vec<i32>,
+            board_items: Vec<i32>,
+            rules_items: Vec<i32>,
+            /// Indices of the toggle items for gravity and limited marks in the menu.
+            gravity_item: i32,
+            limited_item: i32,
+            think_items: Vec<i32>,
+            zoom_items: Vec<i32>,
+            sender: app::Sender<Message>,
+        }
+
+        impl Gui {
+            /// Switch to the theme with the given index in `themes`.
+            fn apply_theme(&mut self, index: usize) {
+                self.theme = index % self.themes.len();
+                let theme = &self.themes[self.theme];
+                self.wind.set_color(Color::from_hex(theme.window));
+                self.grid.set_theme(theme);
+                self.moves.set_theme(theme);
+                self.watch.set_theme(theme);
+                self.status.set_theme(theme);
+                check_radio_item(&self.menu, &self.theme_items, self.theme);
+                self.wind.redraw();
+            }
+
+            /// Scale the whole GUI by `zoom` and lay the window out again.
+            fn apply_zoom(&mut self, zoom: f32) {
+                set_zoom(zoom);
+                match ZOOMS.iter().position(|(_, preset)| *preset == zoom) {
+                    Some(index) => check_radio_item(&self.menu, &self.zoom_items, index),
+                    // a zoom from `ZOOM_VARIABLE` that is not in the menu
+                    None => check_radio_item(&self.menu, &self.zoom_items, ZOOMS.len()),
+                }
+                let (w, h) = (self.wind.w(), self.wind.h());
+                layout_window(&mut self.menu, &mut self.grid, &mut self.moves, &mut self.watch, &mut self.status, w, h);
+                self.wind.redraw();
+            }
+
+            /// Replace the board grid by one for `dims`.
+            fn set_board(&mut self, dims: &Dimensions) {
+                let grid = build_grid(&mut self.wind, dims, self.sender);
+                let old_grid = std::mem::replace(&mut self.grid, grid);
+                self.wind.remove(&*old_grid);
+                app::delete_widget((*old_grid).clone());
+                self.grid.set_theme(&self.themes[self.theme]);
+                if let Some(index) = board_sizes().iter().position(|(_, size)| size == dims) {
+                    check_radio_item(&self.menu, &self.board_items, index);
+                }
+                self.install_layout();
+                self.wind.redraw();
+            }
+
+            /// Lay out the window now and whenever it is resized.
+            fn install_layout(&mut self) {
+                let mut menu = self.menu.clone();
+                let mut grid = self.grid.clone();
+                let mut moves = self.moves.clone();
+                let mut watch = self.watch.clone();
+                let mut status = self.status.clone();
+                self.wind.resize_callback(move |_, _, _, w, h| layout_window(&mut menu, &mut grid, &mut moves, &mut watch, &mut status, w, h));
+                let (w, h) = (self.wind.w(), self.wind.h());
+                layout_window(&mut self.menu, &mut self.grid, &mut self.moves, &mut self.watch, &mut self.status, w, h);
+            }
+
+            /// Show the controls for watching the AI below the move list or hide them.
+            fn show_watch(&mut self, show: bool) {
+                if show {
+                    self.watch.show();
+                }
+                else {
+                    self.watch.hide();
+                }
+                let (w, h) = (self.wind.w(), self.wind.h());
+                layout_window(&mut self.menu, &mut self.grid, &mut self.moves, &mut self.watch, &mut self.status, w, h);
+                self.wind.redraw();
+            }
+        }
+
+        /// Stack the menu bar, the board with the move list (and the controls for watching the
+        /// AI, if shown) to its right and the status panel below each other.
+        fn layout_window(menu: &mut MenuBar, grid: &mut BoardGrid, moves: &mut MoveList, watch: &mut WatchPanel, status: &mut StatusPanel, w: i32, h: i32) {
+            let status_h = (h - MENU_HEIGHT) / 6;
+            let board_h = h - MENU_HEIGHT - status_h;
+            let moves_w = w / 4;
+            let watch_h = if watch.visible() { (board_h / 2).min(160) } else { 0 };
+            menu.resize(0, 0, w, MENU_HEIGHT);
+            grid.resize(0, MENU_HEIGHT, w - moves_w, board_h);
+            moves.resize(w - moves_w, MENU_HEIGHT, moves_w, board_h - watch_h);
+            watch.resize(w - moves_w, MENU_HEIGHT + board_h - watch_h, moves_w, watch_h);
+            status.resize(0, h - status_h, w, status_h);
+        }
+
+        /// Create a board grid for `dims` in the window whose cells and keys send their
+        /// messages through `s`.
+        fn build_grid(wind: &mut Window, dims: &Dimensions, s: app::Sender<Message>) -> BoardGrid {
+            wind.begin();
+            let mut grid = BoardGrid::new(0, MENU_HEIGHT, wind.w(), wind.w(), dims.rows, dims.columns);
+            wind.end();
+            for (cell_number, but) in grid.buttons_mut().iter_mut().enumerate() {
+                but.emit(s, Message::Number(cell_number));
+            }
+            grid.focus(dims.center());
+            grid.set_ghost(Field::Cross);
+            let mut key_grid = grid.clone();
+            grid.handle(move |_, ev| {
+                if ev != Event::KeyDown {
+                    return false;
+                }
+                let key = app::event_key();
+                // the grid is laid out like a numeric keypad, so on the classic board the
+                // digits map directly onto the cells
+                if let Some(digit) = digit_key(key) {
+                    if key_grid.rows() == 3 && key_grid.columns() == 3 {
+                        s.send(Message::Number(digit - 1));
+                        return true;
+                    }
+                }
+                match key {
+                    Key::Up => key_grid.move_focus(1, 0),
+                    Key::Down => key_grid.move_focus(-1, 0),
+                    Key::Left => key_grid.move_focus(0, -1),
+                    Key::Right => key_grid.move_focus(0, 1),
+                    Key::Enter | Key::KPEnter => {
+                        if let Some(cell) = key_grid.focused() {
+                            s.send(Message::Number(cell));
+                        }
+                    }
+                    _ => match key.to_char() {
+                        Some('n') => s.send(Message::NewGame),
+                        Some('u' | 'z') => s.send(Message::Undo),
+                        Some('h') => s.send(Message::Hint),
+                        Some('t') => s.send(Message::NextTheme),
+                        _ => return false,
+                    },
+                }
+                true
+            });
+            grid
+        }
+
+        /// Create the menu bar. Every entry sends a message through `s`, the indices of the
+        /// radio items are stored in `gui`.
+        fn build_menu(gui: &mut Gui) {
+            let s = gui.sender;
+            let menu = &mut gui.menu;
+            menu.add_emit("Game/New", Shortcut::Ctrl | 'n', MenuFlag::Normal, s, Message::NewGame);
+            menu.add_emit("Game/Save...", Shortcut::Ctrl | 's', MenuFlag::Normal, s, Message::Save);
+            menu.add_emit("Game/Load...", Shortcut::Ctrl | 'o', MenuFlag::MenuDivider, s, Message::Load);
+            menu.add_emit("Game/Undo", Shortcut::Ctrl | 'z', MenuFlag::Normal, s, Message::Undo);
+            menu.add_emit("Game/Hint", Shortcut::None | 'h', MenuFlag::MenuDivider, s, Message::Hint);
+            menu.add_emit("Game/Quit", Shortcut::Ctrl | 'q', MenuFlag::Normal, s, Message::Quit);
+            for (name, mode) in MODES {
+                let item = menu.add_emit(&format!("Mode/{name}"), Shortcut::None, MenuFlag::Radio, s, Message::SetMode(mode));
+                gui.mode_items.push(item);
+            }
+            for (difficulty, name) in DIFFICULTIES.iter().enumerate() {
+                let item = menu.add_emit(&format!("Difficulty/{name}"), Shortcut::None, MenuFlag::Radio, s, Message::SetDifficulty(difficulty as i32));
+                gui.difficulty_items.push(item);
+            }
+            for (index, theme) in gui.themes.iter().enumerate() {
+                let item = menu.add_emit(&format!("Settings/Theme/{}", menu_label(&theme.name)), Shortcut::None, MenuFlag::Radio, s, Message::SetTheme(index));
+                gui.theme_items.push(item);
+            }
+            menu.add_emit("Settings/Next theme", Shortcut::None | 't', MenuFlag::MenuDivider, s, Message::NextTheme);
+            for (name, dims) in board_sizes() {
+                let item = menu.add_emit(&format!("Settings/Board size/{name}"), Shortcut::None, MenuFlag::Radio, s, Message::SetBoard(dims));
+                gui.board_items.push(item);
+            }
+            for (name, misere) in RULES {
+                let flag = if misere { MenuFlag::Radio | MenuFlag::MenuDivider } else { MenuFlag::Radio };
+                let item = menu.add_emit(&format!("Settings/Rules/{name}"), Shortcut::None, flag, s, Message::SetMisere(misere));
+                gui.rules_items.push(item);
+            }
+            gui.gravity_item = menu.add_emit("Settings/Rules/Gravity (marks fall down)", Shortcut::None, MenuFlag::Toggle, s, Message::ToggleGravity);
+            gui.limited_item = menu.add_emit("Settings/Rules/Limited marks (oldest vanishes)", Shortcut::None, MenuFlag::Toggle, s, Message::ToggleLimited);
+            for (name, seconds) in THINK_TIMES {
+                let item = menu.add_emit(&format!("Settings/AI think time/{name}"), Shortcut::None, MenuFlag::Radio, s, Message::SetThinkTime(seconds));
+                gui.think_items.push(item);
+            }
+            for (name, zoom) in ZOOMS {
+                let item = menu.add_emit(&format!("Settings/Zoom/{name}"), Shortcut::None, MenuFlag::Radio, s, Message::SetZoom(zoom));
+                gui.zoom_items.push(item);
+            }
+            for (name, kind) in VARIANTS {
+                menu.add_emit(&format!("Variants/{name}"), Shortcut::None, MenuFlag::Normal, s, Message::OpenVariant(kind));
+            }
+        }
+
+        /// Create the main window with a menu bar, a board grid for `dims` and a status panel.
+        fn build_window(dims: &Dimensions, s: app::Sender<Message>) -> Gui {
+            // start with two thirds of the smaller screen side, but not smaller than 300 pixels
+            let (_, _, work_w, work_h) = app::screen_work_area(0);
+            let win_w = (work_w.min(work_h) * 2 / 3).max(300);
+            // the board is square, the move list takes a quarter of the width and the status panel
+            // a sixth of the height below the menu
+            let win_h = MENU_HEIGHT + win_w * 6 / 5;
+            let moves_w = win_w / 3;
 
             let mut wind = Window::default()
                 .with_label("FLTK Tic-Tac-Toe")
-                .with_size(win_w, win_h)
+                .with_size(win_w + moves_w, win_h)
                 .center_screen()
                 .with_label("Tic-Tac-Toe");
-            wind.set_color(Color::Light3);
+            let menu = MenuBar::new(0, 0, win_w + moves_w, MENU_HEIGHT, "");
+            let mut moves = MoveList::new(win_w, MENU_HEIGHT, moves_w, win_w);
+            let mut watch = WatchPanel::new(win_w, MENU_HEIGHT + win_w / 2, moves_w, win_w / 2, &DIFFICULTIES, DEFAULT_WATCH_SPEED, s);
+            watch.hide();
+            let status = StatusPanel::new(0, MENU_HEIGHT + win_w, win_w + moves_w, win_h - MENU_HEIGHT - win_w);
+            wind.make_resizable(true);
+            wind.size_range(200, 180 + MENU_HEIGHT, 0, 0);
+            wind.end();
+            let grid = build_grid(&mut wind, dims, s);
+            moves.set_callback(move |list| {
+                if let Ok(moves) = usize::try_from(list.value() - 1) {
+                    s.send(Message::ShowPosition(moves));
+                }
+            });
 
-            // create board
-            let vertical_pack = Pack::new(0, 0, win_w, win_h, "");
+            let mut themes = Theme::built_in();
+            let mut theme = 0;
+            match Theme::load_user_theme() {
+                Some(Ok(user_theme)) => {
+                    themes.push(user_theme);
+                    theme = themes.len() - 1;
+                }
+                Some(Err(err)) => eprintln!("Could not load theme: {err}"),
+                None => {}
+            }
+            let mut gui = Gui {
+                wind,
+                menu,
+                grid,
+                moves,
+                watch,
+                status,
+                themes,
+                theme,
+                mode_items: vec![],
+                difficulty_items: vec![],
+                theme_items: vec![],
+                board_items: vec![],
+                rules_items: vec![],
+                gravity_item: 0,
+                limited_item: 0,
+                think_items: vec![],
+                zoom_items: vec![],
+                sender: s,
+            };
+            build_menu(&mut gui);
+            gui.install_layout();
+            gui.apply_theme(theme);
+            if let Some(index) = board_sizes().iter().position(|(_, size)| size == dims) {
+                check_radio_item(&gui.menu, &gui.board_items, index);
+            }
+            check_radio_item(&gui.menu, &gui.think_items, DEFAULT_THINK_TIME);
+            check_radio_item(&gui.menu, &gui.rules_items, 0);
+            gui.apply_zoom(zoom_from_env());
+            gui.wind.show();
+            gui
+        }
 
-            let mut horizontal_pack = Pack::new(0, 0, win_w, 200, "");
-            let mut but7 = MyButton::new();
-            let mut but8 = MyButton::new();
-            let mut but9 = MyButton::new();
-            horizontal_pack.end();
-            horizontal_pack.set_type(PackType::Horizontal);
+        /// Everything belonging to the running session: the window, the current game, the score
+        /// and the chosen settings.
+        struct Table {
+            gui: Gui,
+            game: Game,
+            score: Score,
+            mode: Mode,
+            rules: Rules,
+            difficulty: i32,
+            /// Whether a `Message::AiTurn` is already on its way.
+            ai_pending: bool,
+            /// Number of moves of the earlier position shown on the grid instead of the game.
+            preview: Option<usize>,
+            /// Difficulties of the AI for crosses and circles when it plays both sides.
+            strategies: [i32; 2],
+            /// Moves per second when the AI plays both sides.
+            speed: f64,
+            paused: bool,
+            /// Number of the request the AI is thinking about. Answers to other requests are
+            /// outdated and ignored.
+            thinking: Option<u64>,
+            /// Number of the next request to the AI.
+            next_request: u64,
+            /// Seconds the AI may think before it has to play a quick move.
+            think_time: f64,
+            /// Position set up on the analysis board.
+            editor: Vec<Field>,
+            /// Solver for hints and the analysis board, which keeps the positions it solved.
+            solver: Solver,
+            /// Open windows of variants by the id their messages carry.
+            variants: WindowList<Box<dyn VariantWindow>>,
+        }
 
-            let mut horizontal_pack = Pack::new(0, 0, win_w, 200, "");
-            let mut but4 = MyButton::new();
-            let mut but5 = MyButton::new();
-            let mut but6 = MyButton::new();
-            horizontal_pack.end();
-            horizontal_pack.set_type(PackType::Horizontal);
+        impl Table {
+            /// Whether the AI plays the marks of `player`.
+            fn is_ai(&self, player: Field) -> bool {
+                match self.mode {
+                    Mode::VersusAi => player == Field::Circle,
+                    Mode::Hotseat | Mode::Analysis => false,
+                    Mode::AiVersusAi => true,
+                }
+            }
 
-            let mut horizontal_pack = Pack::new(0, 0, win_w, 200, "");
-            let mut but1 = MyButton::new();
-            let mut but2 = MyButton::new();
-            let mut but3 = MyButton::new();
+            /// Name of the side playing `player` in the status panel.
+            fn player_name(&self, player: Field) -> &'static str {
+                match (self.mode, player) {
+                    (Mode::VersusAi, Field::Cross) => "You",
+                    (Mode::VersusAi, _) => "AI",
+                    (Mode::Hotseat | Mode::Analysis, Field::Cross) => "X",
+                    (Mode::Hotseat | Mode::Analysis, _) => "O",
+                    (Mode::AiVersusAi, Field::Cross) => "AI (X)",
+                    (Mode::AiVersusAi, _) => "AI (O)",
+                }
+            }
 
-            horizontal_pack.end();
-            horizontal_pack.set_type(PackType::Horizontal);
+            /// Show the state of the game and the score in the status panel.
+            fn update_status(&mut self) {
+                let game = &self.game;
+                let turn = match game.outcome() {
+                    _ if self.mode == Mode::Analysis => "Analysis board: click a cell to cycle through X, O and free".to_string(),
+                    Some(Outcome::Won(player)) if self.rules.misere => format!("{} completed a line and lost!", self.player_name(opponent(player))),
+                    Some(Outcome::Won(Field::Cross)) if self.mode == Mode::VersusAi => "You have won!".to_string(),
+                    Some(Outcome::Won(player)) => format!("{} has won!", self.player_name(player)),
+                    Some(Outcome::Draw) => "Cats game! Remis!".to_string(),
+                    None => {
+                        let turn = match (self.mode, game.to_move()) {
+                            (Mode::VersusAi, Field::Cross) => "Your turn (X)".to_string(),
+                            (_, player) if self.thinking.is_some() => format!("{} is thinking...", self.player_name(player)),
+                            (_, player) => format!("{} to move", self.player_name(player)),
+                        };
+                        let difficulty = match self.mode {
+                            Mode::VersusAi => format!("   Difficulty: {}", DIFFICULTIES[self.difficulty as usize]),
+                            Mode::Hotseat | Mode::Analysis => String::new(),
+                            Mode::AiVersusAi => format!(
+                                "   X: {} vs. O: {}",
+                                DIFFICULTIES[self.strategies[0] as usize],
+                                DIFFICULTIES[self.strategies[1] as usize]
+                            ),
+                        };
+                        let (label, last_move) = match self.mode {
+                            Mode::VersusAi => ("Last AI move", game.history().iter().rev().find(|cell| game.board()[**cell] == Field::Circle)),
+                            _ => ("Last move", game.history().last()),
+                        };
+                        let last_move = match last_move {
+                            Some(cell) => game.dims().cell_name(*cell),
+                            None => "-".to_string(),
+                        };
+                        let misere = if self.rules.misere { "   Misère" } else { "" };
+                        let gravity = if self.rules.gravity { "   Gravity" } else { "" };
+                        let limited = if self.rules.limited { "   Limited marks" } else { "" };
+                        format!("{turn}{difficulty}{misere}{gravity}{limited}   {label}: {last_move}")
+                    }
+                };
+                let score = format!(
+                    "Score   {}: {}   {}: {}   Cats games: {}",
+                    self.player_name(Field::Cross),
+                    self.score.crosses,
+                    self.player_name(Field::Circle),
+                    self.score.circles,
+                    self.score.draws
+                );
+                self.gui.status.set_turn(&turn);
+                self.gui.status.set_score(&score);
+                let moves: Vec<String> = game
+                    .history()
+                    .iter()
+                    .map(|cell| {
+                        let mark = if game.board()[*cell] == Field::Cross { "X" } else { "O" };
+                        format!("{mark} {}", game.dims().cell_name(*cell))
+                    })
+                    .collect();
+                self.gui.moves.set_moves(&moves, self.preview.unwrap_or(moves.len()));
+            }
 
+            /// Show the position after the first `moves` moves on the grid. The game itself is
+            /// not changed and continues as soon as the last move is selected again.
+            fn show_position(&mut self, moves: usize) {
+                if moves >= self.game.history().len() {
+                    self.show_live();
+                    return;
+                }
+                let mut position = Game::with_rules(*self.game.dims(), *self.game.rules());
+                for cell in &self.game.history()[..moves] {
+                    // the moves were legal when they were played
+                    let _ = position.play(*cell);
+                }
+                self.preview = Some(moves);
+                self.gui.grid.show_board(position.board());
+                self.gui.grid.set_ghost(Field::Free);
+                self.gui.grid.set_fading(None);
+                self.gui.status.set_message("Showing an earlier position. Select the last move to continue.");
+            }
 
-            vertical_pack.end();
+            /// Show the running game again after an earlier position was shown.
+            fn show_live(&mut self) {
+                if self.preview.take().is_none() {
+                    return;
+                }
+                self.gui.grid.show_board(self.game.board());
+                if let Some(line) = self.game.winning_line() {
+                    self.gui.grid.highlight_line(&line);
+                }
+                self.gui.status.set_message("");
+                self.update_ghost();
+            }
 
-            wind.make_resizable(false);
-            wind.end();
-            wind.show();
+            /// Preview the mark of a human player to move when hovering free cells and fade the
+            /// mark that vanishes with their move.
+            fn update_ghost(&mut self) {
+                let player = self.game.to_move();
+                let live = self.game.outcome().is_none() && self.preview.is_none() && self.mode != Mode::Analysis;
+                self.gui.grid.set_fading(if live { self.game.next_to_vanish(player) } else { None });
+                if self.mode == Mode::Analysis {
+                    self.gui.grid.set_ghost(Field::Cross);
+                }
+                // with gravity the mark lands elsewhere than the hovered cell
+                else if self.game.outcome().is_none() && !self.is_ai(player) && self.preview.is_none() && !self.rules.gravity {
+                    self.gui.grid.set_ghost(player);
+                }
+                else {
+                    self.gui.grid.set_ghost(Field::Free);
+                }
+            }
 
-            app::set_focus(&*but1); // maybe remove this later
+            /// Ask for an AI move if it is the AI's turn. When the AI plays both sides, it
+            /// waits a moment so that its moves can be followed, and starts the next game
+            /// once a game is over.
+            fn request_ai_turn(&mut self) {
+                let watching = self.mode == Mode::AiVersusAi;
+                if self.ai_pending || self.thinking.is_some() || (watching && self.paused) {
+                    return;
+                }
+                if (self.game.outcome().is_some() && !watching) || !self.is_ai(self.game.to_move()) {
+                    return;
+                }
+                self.ai_pending = true;
+                let s = self.gui.sender;
+                if watching {
+                    let moves = if self.game.outcome().is_some() { MOVES_BETWEEN_GAMES } else { 1.0 };
+                    app::add_timeout3(moves / self.speed, move |_| s.send(Message::AiTurn));
+                }
+                else {
+                    s.send(Message::AiTurn);
+                }
+            }
 
-            let but_vectors = vec![
-                &mut but1, &mut but2, &mut but3, &mut but4, &mut but5, &mut but6, &mut but7, &mut but8, &mut but9, 
-            ];
+            /// Update the marks on the grid that differ from the game, except the one on `cell`,
+            /// e.g. a mark that vanished with a move or came back when it was taken back.
+            fn sync_marks(&mut self, cell: Option<usize>) {
+                let board = self.game.board().to_vec();
+                for (index, mark) in board.into_iter().enumerate() {
+                    if Some(index) != cell && self.gui.grid.buttons()[index].mark() != mark {
+                        self.gui.grid.set_mark(index, mark);
+                    }
+                }
+            }
 
-            let (s, r) = app::channel::<Message>();
-            for (cell_number, but) in but_vectors.into_iter().enumerate() {
-                but.emit(s, Message::Number(cell_number));
+            /// Show a move that was just made and continue with the next player.
+            fn after_move(&mut self, cell: usize) {
+                if self.preview.is_none() && self.rules.limited {
+                    self.sync_marks(Some(cell));
+                }
+                // while an earlier position is shown, the grid is updated when the game is
+                // shown again
+                if self.preview.is_none() && self.rules.gravity {
+                    self.gui.grid.drop_mark(cell, self.game.board()[cell]);
+                }
+                else if self.preview.is_none() {
+                    self.gui.grid.set_mark(cell, self.game.board()[cell]);
+                }
+                if let Some(outcome) = self.game.outcome() {
+                    self.score.record(outcome);
+                    if self.preview.is_none() {
+                        if let Some(line) = self.game.winning_line() {
+                            self.gui.grid.highlight_line(&line);
+                        }
+                        self.gui.status.set_message("Press N for a new game.");
+                    }
+                }
+                self.update_ghost();
+                self.request_ai_turn();
             }
-            let mut but_vectors = vec![
-                &mut but1, &mut but2, &mut but3, &mut but4, &mut but5, &mut but6, &mut but7, &mut but8, &mut but9, 
-            ];
-            let mut board = vec![];
-            for _ in 0..9 {
-                let v = Field::Free;
-                board.push(v);
-            }
-            let mut results = check_functions::someone_has_won(&board);
-            let last_turn = 5;
-            let mut turn = 1;
-
-            let center = ((app::screen_size().0 / 2.0) as i32, (app::screen_size().1 / 2.0) as i32);
-            let difficulty_message = "Choose difficulty".to_string();
-            let difficulty = dialog::choice2(center.0 - 200, center.1 - 100, &difficulty_message, "Easy", "Challenge", "Hard");
-            let mut chosen_difficulty = 0;
-            match difficulty {
-                Some(d) => {chosen_difficulty = d;},
-                None => {dialog::alert(center.0 - 200, center.1 - 100, "Difficulty was set to easiest because you did not choose a difficulty yourself.")},
-            };
-            while app.wait() {
-                if cats_game(&board) {
-                    println!("Cats game! Remis!");
-                    break;
-                }
-                // Step two, ask where the user wants to place their cross
-                if let Some(val) = r.recv() {
-                    match val {
-                        Message::Number(num) => {
-                            let answer = num;
-                            match board.get(answer){
-                                Some(Field::Cross) => {dialog::alert(center.0 - 200, center.1 - 100, "You have already taken this field!"); continue;},
-                                Some(Field::Circle) => {dialog::alert(center.0 - 200, center.1 - 100, "Your opponent has already taken this field!"); continue;},
-                                Some(Field::Free) => {
-                                    board[answer] = Field::Cross;
-                                    but_vectors[answer].set_label("X");
-                                },
-                                None => (),
-                            }
+
+            /// Start a new game with the current board size.
+            fn new_game(&mut self, dims: Dimensions) {
+                // the winning line of the last game stays highlighted until now
+                self.game = Game::with_rules(dims, self.rules);
+                self.preview = None;
+                self.stop_thinking();
+                self.gui.grid.clear();
+                self.gui.status.set_message("");
+                self.update_ghost();
+                self.request_ai_turn();
+                if self.mode == Mode::Analysis {
+                    self.set_position(vec![Field::Free; dims.cells()]);
+                }
+            }
+
+            /// Show a position on the analysis board.
+            fn set_position(&mut self, board: Vec<Field>) {
+                self.editor = board;
+                self.gui.grid.show_board(&self.editor);
+                self.analyse();
+            }
+
+            /// Change a cell of the analysis board from free to cross to circle and back to
+            /// free, regardless of whose turn it is.
+            fn edit_cell(&mut self, cell: usize) {
+                let mut board = self.editor.clone();
+                board[cell] = match board[cell] {
+                    Field::Free => Field::Cross,
+                    Field::Cross => Field::Circle,
+                    Field::Circle => Field::Free,
+                };
+                self.set_position(board);
+            }
+
+            /// Validate the position on the analysis board and show the solver's value of every
+            /// free cell as a heat map.
+            fn analyse(&mut self) {
+                let dims = *self.game.dims();
+                self.gui.grid.set_overlays(&[]);
+                if self.rules.limited {
+                    // the board alone does not tell which marks vanish next
+                    self.gui.status.set_message("Positions with limited marks cannot be analysed.");
+                    return;
+                }
+                let to_move = match validate_position(&self.editor, &dims, &self.rules) {
+                    Ok(player) => player,
+                    Err(err) => {
+                        self.gui.status.set_message(&format!("Invalid position: {err}."));
+                        return;
+                    }
+                };
+                if let (Some(line), Some(player)) = (winning_line(&self.editor, &dims), winner(&self.editor, &dims, &self.rules)) {
+                    self.gui.grid.highlight_line(&line);
+                    self.gui.status.set_message(&format!("{} has won in this position.", self.player_name(player)));
+                    return;
+                }
+                if cats_game(&self.editor) {
+                    self.gui.status.set_message("The board is full.");
+                    return;
+                }
+                if dims != Dimensions::default() {
+                    self.gui.status.set_message("The solver can only analyse the 3x3 board.");
+                    return;
+                }
+                // faster wins and slower losses get stronger colours
+                let free = self.editor.iter().filter(|cell| **cell == Field::Free).count() as f64;
+                let overlays: Vec<Option<Overlay>> = self
+                    .solver
+                    .evaluate_moves(&self.editor, to_move)
+                    .into_iter()
+                    .map(|value| {
+                        value.map(|value| {
+                            let caption = match value.cmp(&0) {
+                                Ordering::Greater => "Win",
+                                Ordering::Equal => "Draw",
+                                Ordering::Less => "Loss",
+                            };
+                            (f64::from(value) / free, caption.to_string())
+                        })
+                    })
+                    .collect();
+                self.gui.grid.set_overlays(&overlays);
+                self.gui.status.set_message(&format!("{} to move. The cells show the result of each move with perfect play.", self.player_name(to_move)));
+            }
+
+            /// Replace the current game, e.g. by a loaded one.
+            fn set_game(&mut self, game: Game) {
+                if game.dims() != self.game.dims() {
+                    self.gui.set_board(game.dims());
+                }
+                self.set_rules(*game.rules());
+                self.new_game(*game.dims());
+                for cell in game.history() {
+                    self.gui.grid.set_mark(*cell, game.board()[*cell]);
+                }
+                if let Some(line) = game.winning_line() {
+                    self.gui.grid.highlight_line(&line);
+                }
+                self.game = game;
+                self.update_ghost();
+                self.request_ai_turn();
+                if self.mode == Mode::Analysis {
+                    self.set_position(self.game.board().to_vec());
+                }
+            }
+
+            /// Handle a click on (or key press for) a cell.
+            fn play_cell(&mut self, cell: usize) {
+                if self.mode == Mode::Analysis {
+                    self.edit_cell(cell);
+                    return;
+                }
+                let player = self.game.to_move();
+                if self.mode == Mode::AiVersusAi {
+                    self.gui.status.set_message("The AI plays both sides. Choose another mode to play yourself.");
+                    return;
+                }
+                if self.game.outcome().is_none() && self.is_ai(player) {
+                    if self.thinking.is_some() {
+                        self.gui.status.set_message("Please wait, the AI is thinking.");
+                    }
+                    return;
+                }
+                if self.preview.is_some() {
+                    // a click on the earlier position only returns to the game
+                    self.show_live();
+                    return;
+                }
+                let dims = *self.game.dims();
+                let cell = match drop_cell(self.game.board(), &dims, cell % dims.columns) {
+                    // a click anywhere in a column drops the mark into it
+                    Some(lowest) if self.rules.gravity => lowest,
+                    None if self.rules.gravity => {
+                        self.gui.status.set_message("This column is full!");
+                        return;
+                    }
+                    _ => cell,
+                };
+                match self.game.play(cell) {
+                    Err(IllegalMove::Occupied(owner)) if owner == player => {
+                        self.gui.status.set_message("You have already taken this field!");
+                    }
+                    Err(IllegalMove::Occupied(_)) => {
+                        self.gui.status.set_message("Your opponent has already taken this field!");
+                    }
+                    Err(IllegalMove::GameOver) => {
+                        self.gui.status.set_message("The game is over. Press N for a new game.");
+                    }
+                    Err(IllegalMove::OutOfBoard | IllegalMove::WrongBoard | IllegalMove::Floating | IllegalMove::AllPlaced | IllegalMove::NotAdjacent | IllegalMove::StillPlacing | IllegalMove::NotYourMark | IllegalMove::DeadBoard | IllegalMove::NumberUnavailable) => {}
+                    Ok(()) => {
+                        self.gui.status.set_message("");
+                        self.after_move(cell);
+                    }
+                }
+            }
+
+            /// Let the AI make its move if it is still its turn.
+            fn play_ai(&mut self) {
+                self.ai_pending = false;
+                if self.mode == Mode::AiVersusAi && self.paused {
+                    return;
+                }
+                self.ai_step();
+            }
+
+            /// Let the AI think about its move on a background thread. When the AI plays both
+            /// sides and the game is over, start the next one instead.
+            ///
+            /// The move arrives as `Message::AiMove`. If it takes longer than `think_time`,
+            /// `Message::AiTimeout` makes the AI play a quick heuristic move instead.
+            fn ai_step(&mut self) {
+                let player = self.game.to_move();
+                if self.game.outcome().is_some() {
+                    if self.mode == Mode::AiVersusAi {
+                        self.new_game(*self.game.dims());
+                    }
+                    return;
+                }
+                if !self.is_ai(player) || self.thinking.is_some() {
+                    return;
+                }
+                let difficulty = match (self.mode, player) {
+                    (Mode::AiVersusAi, Field::Cross) => self.strategies[0],
+                    (Mode::AiVersusAi, _) => self.strategies[1],
+                    _ => self.difficulty,
+                };
+                let request = self.next_request;
+                self.next_request += 1;
+                self.thinking = Some(request);
+                self.gui.wind.set_cursor(Cursor::Wait);
+                let s = self.gui.sender;
+                if self.rules.limited {
+                    // the search needs the order of the marks, and stops in time on its own
+                    let game = self.game.clone();
+                    let think_time = Duration::from_secs_f64(self.think_time * 0.8);
+                    thread::spawn(move || s.send(Message::AiMove(request, limited::search_move(&game, difficulty, think_time))));
+                    app::add_timeout3(self.think_time, move |_| s.send(Message::AiTimeout(request)));
+                    return;
+                }
+                let board = self.game.board().to_vec();
+                let dims = *self.game.dims();
+                let rules = self.rules;
+                thread::spawn(move || {
+                    let spot = choose_move(&board, &dims, &rules, player, difficulty);
+                    s.send(Message::AiMove(request, spot));
+                });
+                app::add_timeout3(self.think_time, move |_| s.send(Message::AiTimeout(request)));
+            }
+
+            /// Forget the request the AI is thinking about, its answer will be ignored.
+            fn stop_thinking(&mut self) {
+                if self.thinking.take().is_some() {
+                    self.gui.wind.set_cursor(Cursor::Default);
+                }
+            }
+
+            /// Play the move the AI chose for `request`, unless the request is outdated.
+            fn finish_ai_move(&mut self, request: u64, spot: Option<usize>) {
+                if self.thinking != Some(request) {
+                    return;
+                }
+                self.stop_thinking();
+                let spot = match spot {
+                    Some(spot) => spot,
+                    None => {
+                        self.gui.status.set_message("The AI ran out of time and played a quick move.");
+                        heuristic_move(self.game.board(), self.game.dims(), &self.rules, self.game.to_move())
+                    }
+                };
+                if self.game.play(spot).is_ok() {
+                    self.after_move(spot);
+                }
+            }
+
+            /// Take back the last move of the player and, against the AI, the AI's answer.
+            fn undo(&mut self) {
+                if self.mode == Mode::AiVersusAi {
+                    self.gui.status.set_message("Moves of the AI playing both sides cannot be taken back.");
+                    return;
+                }
+                if self.thinking.is_some() {
+                    self.gui.status.set_message("Please wait, the AI is thinking.");
+                    return;
+                }
+                if self.game.outcome().is_some() {
+                    self.gui.status.set_message("The game is over. Press N for a new game.");
+                    return;
+                }
+                self.show_live();
+                while let Some(cell) = self.game.undo() {
+                    self.gui.grid.set_mark(cell, Field::Free);
+                    if !self.is_ai(self.game.to_move()) {
+                        break;
+                    }
+                }
+                // marks that vanished with the moves come back
+                self.sync_marks(None);
+                self.gui.status.set_message("");
+                self.update_ghost();
+            }
+
+            /// Move the focus ring to a good move for the player to move.
+            fn hint(&mut self) {
+                let player = self.game.to_move();
+                if self.game.outcome().is_some() || self.is_ai(player) || self.mode == Mode::Analysis {
+                    return;
+                }
+                self.show_live();
+                let dims = *self.game.dims();
+                let hint = if self.rules.limited {
+                    Some(limited::search_move(&self.game, 2, Duration::from_millis(HINT_TIME)))
+                }
+                else if dims == Dimensions::default() {
+                    self.solver.best_move(self.game.board(), player)
+                }
+                else {
+                    Some(heuristic_move(self.game.board(), &dims, &self.rules, player))
+                };
+                if let Some(hint) = hint {
+                    self.gui.grid.focus(hint);
+                    self.gui.status.set_message(&format!("Hint: {}", dims.cell_name(hint)));
+                }
+            }
+
+            /// Use other rules from the next game on, the score starts again.
+            fn set_rules(&mut self, rules: Rules) {
+                if rules == self.rules {
+                    return;
+                }
+                self.rules = rules;
+                self.solver = Solver::with_rules(rules);
+                self.score = Score::default();
+                if let Some(index) = RULES.iter().position(|(_, misere)| *misere == rules.misere) {
+                    check_radio_item(&self.gui.menu, &self.gui.rules_items, index);
+                }
+                for (item, on) in [(self.gui.gravity_item, rules.gravity), (self.gui.limited_item, rules.limited)] {
+                    if let Some(mut item) = self.gui.menu.at(item) {
+                        if on {
+                            item.set();
                         }
-                        Message::NewGame => {
-                            println!("Not implemented yet!");
+                        else {
+                            item.clear();
                         }
                     }
-                    results = someone_has_won(&board);
-                    if results.0 {
-                        break;
+                }
+            }
+
+            /// Write the current game to a file chosen by the player.
+            fn save(&mut self) {
+                if self.mode == Mode::Analysis {
+                    self.gui.status.set_message("Only games can be saved, not positions of the analysis board.");
+                    return;
+                }
+                let mut chooser = dialog::NativeFileChooser::new(dialog::NativeFileChooserType::BrowseSaveFile);
+                chooser.set_title("Save game");
+                chooser.set_option(dialog::NativeFileChooserOptions::SaveAsConfirm);
+                chooser.show();
+                let path = chooser.filename();
+                if path.as_os_str().is_empty() {
+                    return;
+                }
+                match std::fs::write(&path, self.game.to_text()) {
+                    Ok(()) => self.gui.status.set_message(&format!("Saved game to {}", path.display())),
+                    Err(err) => self.gui.status.set_message(&format!("Could not save game: {err}")),
+                }
+            }
+
+            /// Continue a game from a file chosen by the player.
+            fn load(&mut self) {
+                let mut chooser = dialog::NativeFileChooser::new(dialog::NativeFileChooserType::BrowseFile);
+                chooser.set_title("Load game");
+                chooser.show();
+                let path = chooser.filename();
+                if path.as_os_str().is_empty() {
+                    return;
+                }
+                let game = std::fs::read_to_string(&path).map_err(|err| err.to_string()).and_then(|text| Game::from_text(&text));
+                match game {
+                    Ok(game) => {
+                        self.set_game(game);
+                        self.gui.status.set_message(&format!("Loaded game from {}", path.display()));
+                    }
+                    Err(err) => self.gui.status.set_message(&format!("Could not load game: {err}")),
+                }
+            }
+
+            /// Open a window with a new game of a variant.
+            fn open_variant(&mut self, kind: VariantKind) {
+                let theme = &self.gui.themes[self.gui.theme];
+                let (difficulty, think_time, sender) = (self.difficulty, self.think_time, self.gui.sender);
+                self.variants.open(|window| -> Box<dyn VariantWindow> {
+                    let s = VariantSender::new(window, sender);
+                    match kind {
+                        VariantKind::Ultimate => Box::new(VariantTable::<Ultimate, UltimateGrid>::open(Ultimate::new(), theme, difficulty, think_time, s)),
+                        VariantKind::Qubic => Box::new(VariantTable::<Qubic, QubicGrid>::open(Qubic::new(), theme, difficulty, think_time, s)),
+                        VariantKind::Gomoku(rule) => Box::new(VariantTable::<Gomoku, GomokuGrid>::open(Gomoku::new(rule), theme, difficulty, think_time, s)),
+                        VariantKind::Morris => Box::new(VariantTable::<Morris, MorrisGrid>::open(Morris::new(), theme, difficulty, think_time, s)),
+                        VariantKind::Wild => Box::new(VariantTable::<Wild, WildGrid>::open(Wild::new(), theme, difficulty, think_time, s)),
+                        VariantKind::Notakto(boards) => Box::new(VariantTable::<Notakto, NotaktoGrid>::open(Notakto::new(boards), theme, difficulty, think_time, s)),
+                        VariantKind::Numerical => Box::new(VariantTable::<Numerical, NumericalGrid>::open(Numerical::new(), theme, difficulty, think_time, s)),
+                    }
+                });
+            }
+
+            /// React to a message of the GUI. Returns `false` if the player wants to quit.
+            fn handle(&mut self, message: Message) -> bool {
+                // the messages of the variant windows are passed on to them
+                let Some(message) = self.variants.dispatch(message) else {
+                    return true;
+                };
+                match message {
+                    Message::Number(cell) => self.play_cell(cell),
+                    Message::AiTurn => self.play_ai(),
+                    Message::AiMove(request, spot) => self.finish_ai_move(request, Some(spot)),
+                    Message::AiTimeout(request) => self.finish_ai_move(request, None),
+                    Message::SetThinkTime(seconds) => {
+                        self.think_time = seconds;
+                        if let Some(index) = THINK_TIMES.iter().position(|(_, time)| *time == seconds) {
+                            check_radio_item(&self.gui.menu, &self.gui.think_items, index);
+                        }
+                    }
+                    Message::SetZoom(zoom) => self.gui.apply_zoom(zoom),
+                    Message::NewGame => self.new_game(*self.game.dims()),
+                    Message::Undo => self.undo(),
+                    Message::Hint => self.hint(),
+                    Message::Save => self.save(),
+                    Message::Load => self.load(),
+                    Message::Quit => return false,
+                    Message::NextTheme => self.gui.apply_theme(self.gui.theme + 1),
+                    Message::SetTheme(theme) => self.gui.apply_theme(theme),
+                    Message::SetMode(mode) => {
+                        // the analysis board starts with the position of the game
+                        let board = self.game.board().to_vec();
+                        // the score counts the games of one mode only
+                        self.mode = mode;
+                        self.score = Score::default();
+                        if let Some(index) = MODES.iter().position(|(_, m)| *m == mode) {
+                            check_radio_item(&self.gui.menu, &self.gui.mode_items, index);
+                        }
+                        self.gui.show_watch(mode == Mode::AiVersusAi);
+                        self.new_game(*self.game.dims());
+                        if mode == Mode::Analysis {
+                            self.set_position(board);
+                        }
+                    }
+                    Message::SetDifficulty(difficulty) => {
+                        self.difficulty = difficulty;
+                        check_radio_item(&self.gui.menu, &self.gui.difficulty_items, difficulty as usize);
                     }
-                    if turn < last_turn {
-                        ai_move(&mut board, &mut but_vectors, chosen_difficulty);
+                    Message::ShowPosition(moves) => self.show_position(moves),
+                    Message::SetStrategy(player, difficulty) => {
+                        let side = usize::from(player != Field::Cross);
+                        self.strategies[side] = difficulty;
                     }
-                    results = someone_has_won(&board);
-                    turn += 1;
-                    if results.0 {
-                       break; 
+                    Message::SetSpeed(speed) => self.speed = speed.clamp(WATCH_SPEEDS.0, WATCH_SPEEDS.1),
+                    Message::TogglePause => {
+                        self.paused = !self.paused;
+                        self.gui.watch.set_paused(self.paused);
+                        self.request_ai_turn();
                     }
+                    Message::Step => {
+                        if self.mode == Mode::AiVersusAi && self.paused {
+                            self.ai_step();
+                        }
+                    }
+                    Message::SetMisere(misere) => {
+                        if misere != self.rules.misere {
+                            self.set_rules(Rules { misere, ..self.rules });
+                            self.new_game(*self.game.dims());
+                        }
+                    }
+                    // gravity and limited marks exclude each other
+                    Message::ToggleGravity => {
+                        self.set_rules(Rules { gravity: !self.rules.gravity, limited: false, ..self.rules });
+                        self.new_game(*self.game.dims());
+                    }
+                    Message::ToggleLimited => {
+                        self.set_rules(Rules { limited: !self.rules.limited, gravity: false, ..self.rules });
+                        self.new_game(*self.game.dims());
+                    }
+                    Message::SetBoard(dims) => {
+                        if dims != *self.game.dims() {
+                            self.gui.set_board(&dims);
+                            self.score = Score::default();
+                            self.new_game(dims);
+                        }
+                    }
+                    Message::OpenVariant(kind) => self.open_variant(kind),
+                    // passed on to their window by `dispatch`
+                    Message::Variant(..) => {}
                 }
+                self.update_status();
+                true
             }
-            let count;
-            let winner_message = match results.1 {
-                Field::Cross => {count = 1; "You have won"},
-                Field::Circle => {count = -1; "AI has won"},
-                Field::Free => {count = 0; "Noone"},
-            };
-            dialog::message(center.0 - 300, center.1 - 100, winner_message);
-            app.quit();
-            count
         }
 
-
-        /// Manages game statistics and start-end game-player interaction.
+        /// Manages game statistics and in-game player interaction.
         pub fn main() {
-            let mut stat_player = 0;
-            let mut stat_ai = 0;
-            let mut stat_remis = 0;
-            let mut new_game: bool = true;
-            let center = ((app::screen_size().0 / 2.0) as i32, (app::screen_size().1 / 2.0) as i32);
-            while new_game {
-                let counter = game_loop();
-                match counter {
-                    1 => stat_player += 1,
-                    -1 => stat_ai += 1,
-                    0 => stat_remis += 1,
-                    _ => {},
-                }
-                let status_message = format!("Current Status:\nPlayer won {} games\n AI won {} games\n {} cat games\nStart a new game? ", stat_player, stat_ai, stat_remis);
-                let answer = dialog::choice2(center.0 - 200, center.1 - 100, &status_message, "Yes", "No", "");
-                match answer {
-                        Some(a) => {
-                            match a {
-                               1  =>  new_game = false,
-                                _ => continue,
-                            }
-                        }
-                        None => new_game = false
+            // create gui
+            let app = app::App::default().with_scheme(app::Scheme::Gleam);
+            set_zoom(zoom_from_env());
+            let dims = Dimensions::default();
+            let (s, r) = app::channel::<Message>();
+            let gui = build_window(&dims, s);
+
+            let mut table = Table {
+                gui,
+                game: Game::new(dims),
+                score: Score::default(),
+                mode: Mode::VersusAi,
+                rules: Rules::default(),
+                difficulty: 0,
+                ai_pending: false,
+                preview: None,
+                strategies: [0, 0],
+                speed: DEFAULT_WATCH_SPEED,
+                paused: false,
+                thinking: None,
+                next_request: 0,
+                think_time: THINK_TIMES[DEFAULT_THINK_TIME].1,
+                editor: vec![Field::Free; dims.cells()],
+                solver: Solver::new(),
+                variants: WindowList::default(),
+            };
+            table.gui.watch.set_paused(false);
+            check_radio_item(&table.gui.menu, &table.gui.mode_items, 0);
+            check_radio_item(&table.gui.menu, &table.gui.difficulty_items, 0);
+            table.update_status();
+            // the only receiver of the shared channel, `handle` passes the messages of the variant
+            // windows on to them
+            while app.wait() {
+                if let Some(message) = r.recv() {
+                    if !table.handle(message) {
+                        break;
+                    }
                 }
+                table.variants.retain(|window| window.is_open());
             }
+            app.quit();
         }
     }
 }
@@ -451,60 +1847,63 @@ pub mod tic_tac_toe {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tic_tac_toe::Field;
+    use crate::tic_tac_toe::{Dimensions, Field};
     use crate::tic_tac_toe::check_functions::someone_has_won;
     use crate::tic_tac_toe::check_functions::cats_game;
     use crate::tic_tac_toe::check_functions::check_for_two;
     use crate::tic_tac_toe::check_functions::check_for_diagonals;
+    use crate::tic_tac_toe::check_functions::winning_line;
+    use crate::tic_tac_toe::check_functions::{available_moves, drop_cell};
+    use crate::tic_tac_toe::Rules;
 
-    // someone_has_won utilizes check_for_three so we are not testing this function seperately
-    // (each test of someone_has_one also tests check_for_three)
+    // someone_has_won utilizes check_for_line so we are not testing this function seperately
+    // (each test of someone_has_one also tests check_for_line)
     #[test]
     fn test_someone_has_won_rows() {
         let board = vec![Field::Cross, Field::Cross, Field::Cross,Field::Free, Field::Free, Field::Free, Field::Free, Field::Free, Field::Free];
-        let results = someone_has_won(&board);
+        let results = someone_has_won(&board, &Dimensions::default());
         assert_eq!(true, results.0);
     }
     #[test]
     fn test_someone_has_won_rows_second() {
         let board = vec![Field::Free, Field::Free, Field::Free, Field::Cross, Field::Cross, Field::Cross, Field::Free, Field::Free, Field::Free];
-        let results = someone_has_won(&board);
+        let results = someone_has_won(&board, &Dimensions::default());
         assert_eq!(true, results.0);
     }
     #[test]
     fn test_someone_has_won_rows_third() {
         let board = vec![Field::Free, Field::Free, Field::Free, Field::Free, Field::Free, Field::Free, Field::Cross, Field::Cross, Field::Cross];
-        let results = someone_has_won(&board);
+        let results = someone_has_won(&board, &Dimensions::default());
         assert_eq!(true, results.0);
     }
     #[test]
     fn test_someone_has_won_cols() {
         let board = vec![Field::Cross, Field::Free, Field::Free,Field::Cross, Field::Free, Field::Free, Field::Cross, Field::Free, Field::Free];
-        let results = someone_has_won(&board);
+        let results = someone_has_won(&board, &Dimensions::default());
         assert_eq!(true, results.0);
     }
     #[test]
     fn test_someone_has_won_cols_second_col() {
         let board = vec![Field::Free, Field::Cross, Field::Free, Field::Free, Field::Cross, Field::Free, Field::Free, Field::Cross, Field::Free];
-        let results = someone_has_won(&board);
+        let results = someone_has_won(&board, &Dimensions::default());
         assert_eq!(true, results.0);
     }
     #[test]
     fn test_someone_has_won_cols_third_col() {
         let board = vec![Field::Free, Field::Free, Field::Cross, Field::Free, Field::Free, Field::Cross, Field::Free, Field::Free, Field::Cross];
-        let results = someone_has_won(&board);
+        let results = someone_has_won(&board, &Dimensions::default());
         assert_eq!(true, results.0);
     }
     #[test]
     fn test_someone_has_won_diags() {
         let board = vec![Field::Cross, Field::Free, Field::Free,Field::Free, Field::Cross, Field::Free, Field::Free, Field::Free, Field::Cross,];
-        let results = someone_has_won(&board);
+        let results = someone_has_won(&board, &Dimensions::default());
         assert_eq!(true, results.0);
     }
     #[test]
     fn test_someone_has_won_diags_other_side() {
         let board = vec![Field::Free, Field::Free, Field::Cross, Field::Free, Field::Cross, Field::Free, Field::Cross, Field::Free, Field::Free];
-        let results = someone_has_won(&board);
+        let results = someone_has_won(&board, &Dimensions::default());
         assert_eq!(true, results.0);
     }
     #[test]
@@ -520,57 +1919,165 @@ mod tests {
     #[test]
     fn test_check_for_two_rows() {
         let board = vec![Field::Cross, Field::Cross, Field::Free,Field::Free, Field::Free, Field::Free, Field::Free, Field::Free, Field::Free];
-        let results = check_for_two(&board, false);
+        let results = check_for_two(&board, &Dimensions::default(), false);
         assert_eq!(true, results.0);
         assert_eq!(2, results.1);
     }
     #[test]
     fn test_check_for_two_rows_second() {
         let board = vec![Field::Free, Field::Free, Field::Free, Field::Cross, Field::Free, Field::Cross, Field::Free, Field::Free, Field::Free];
-        let results = check_for_two(&board, false);
+        let results = check_for_two(&board, &Dimensions::default(), false);
         assert_eq!(true, results.0);
         assert_eq!(4, results.1);
     }
     #[test]
     fn test_check_for_two_rows_third() {
         let board = vec![Field::Free, Field::Free, Field::Free, Field::Free, Field::Free, Field::Free, Field::Cross, Field::Cross, Field::Free];
-        let results = check_for_two(&board, false);
+        let results = check_for_two(&board, &Dimensions::default(), false);
         assert_eq!(true, results.0);
         assert_eq!(8, results.1);
     }
     #[test]
     fn test_check_for_two_cols() {
         let board = vec![Field::Cross, Field::Free, Field::Free,Field::Free, Field::Free, Field::Free, Field::Cross, Field::Free, Field::Free];
-        let results = check_for_two(&board, true);
+        let results = check_for_two(&board, &Dimensions::default(), true);
         assert_eq!(true, results.0);
         assert_eq!(3, results.1);
     }
     #[test]
     fn test_check_for_two_cols_second_col() {
         let board = vec![Field::Free, Field::Cross, Field::Free, Field::Free, Field::Cross, Field::Free, Field::Free, Field::Free, Field::Free];
-        let results = check_for_two(&board, true);
+        let results = check_for_two(&board, &Dimensions::default(), true);
         assert_eq!(true, results.0);
         assert_eq!(7, results.1);
     }
     #[test]
     fn test_check_for_two_cols_third_col() {
         let board = vec![Field::Free, Field::Free, Field::Free, Field::Free, Field::Free, Field::Cross, Field::Free, Field::Free, Field::Cross];
-        let results = check_for_two(&board, true);
+        let results = check_for_two(&board, &Dimensions::default(), true);
         assert_eq!(true, results.0);
         assert_eq!(2, results.1)
     }
     #[test]
     fn test_check_for_diagonals() {
         let board = vec![Field::Cross, Field::Free, Field::Free,Field::Free, Field::Cross, Field::Free, Field::Free, Field::Free, Field::Free];
-        let results = check_for_diagonals(&board);
+        let results = check_for_diagonals(&board, &Dimensions::default());
         assert_eq!(true, results.0);
         assert_eq!(8, results.1);
     }
     #[test]
     fn test_check_for_diagonals_other_side() {
         let board = vec![Field::Free, Field::Free, Field::Free, Field::Free, Field::Cross, Field::Free, Field::Cross, Field::Free, Field::Free];
-        let results = check_for_diagonals(&board);
+        let results = check_for_diagonals(&board, &Dimensions::default());
         assert_eq!(true, results.0);
         assert_eq!(2, results.1)
     }
+    #[test]
+    fn test_number_of_lines() {
+        assert_eq!(8, Dimensions::default().lines().len());
+        assert_eq!(10, Dimensions::new(4, 4, 4).lines().len());
+        assert_eq!(28, Dimensions::new(5, 5, 4).lines().len());
+        assert_eq!(572, Dimensions::new(15, 15, 5).lines().len());
+    }
+    #[test]
+    fn test_someone_has_won_four_in_a_row() {
+        let dims = Dimensions::new(5, 5, 4);
+        let mut board = vec![Field::Free; 25];
+        for i in [6, 7, 8] {
+            board[i] = Field::Circle;
+        }
+        assert!(!someone_has_won(&board, &dims).0);
+        board[9] = Field::Circle;
+        assert_eq!((true, Field::Circle), someone_has_won(&board, &dims));
+    }
+    #[test]
+    fn test_someone_has_won_anti_diagonal_does_not_wrap() {
+        let dims = Dimensions::new(4, 4, 3);
+        let mut board = vec![Field::Free; 16];
+        // 3, 6 and 9 are an anti-diagonal, 1, 4 and 7 would wrap around the edge
+        for i in [1, 4, 7] {
+            board[i] = Field::Cross;
+        }
+        assert!(!someone_has_won(&board, &dims).0);
+        let mut board = vec![Field::Free; 16];
+        for i in [3, 6, 9] {
+            board[i] = Field::Cross;
+        }
+        assert!(someone_has_won(&board, &dims).0);
+    }
+    #[test]
+    fn test_check_for_two_ignores_mixed_lines() {
+        let board = vec![Field::Cross, Field::Circle, Field::Free,Field::Free, Field::Free, Field::Free, Field::Free, Field::Free, Field::Free];
+        let results = check_for_two(&board, &Dimensions::default(), false);
+        assert!(!results.0);
+    }
+    #[test]
+    fn test_check_for_two_four_by_four() {
+        let dims = Dimensions::new(4, 4, 4);
+        let mut board = vec![Field::Free; 16];
+        for i in [1, 5, 13] {
+            board[i] = Field::Cross;
+        }
+        let results = check_for_two(&board, &dims, true);
+        assert!(results.0);
+        assert_eq!(9, results.1);
+    }
+    #[test]
+    fn test_check_for_diagonals_four_by_four() {
+        let dims = Dimensions::new(4, 4, 4);
+        let mut board = vec![Field::Free; 16];
+        for i in [0, 5, 15] {
+            board[i] = Field::Circle;
+        }
+        let results = check_for_diagonals(&board, &dims);
+        assert!(results.0);
+        assert_eq!(10, results.1);
+    }
+    #[test]
+    fn test_winning_line() {
+        let board = vec![Field::Free, Field::Free, Field::Circle, Field::Free, Field::Circle, Field::Free, Field::Circle, Field::Free, Field::Cross];
+        assert_eq!(Some(vec![2, 4, 6]), winning_line(&board, &Dimensions::default()));
+    }
+    #[test]
+    fn test_no_winning_line() {
+        let board = vec![Field::Cross, Field::Circle, Field::Cross, Field::Free, Field::Free, Field::Free, Field::Free, Field::Free, Field::Free];
+        assert_eq!(None, winning_line(&board, &Dimensions::default()));
+    }
+    #[test]
+    fn test_cell_name() {
+        let dims = Dimensions::new(5, 5, 4);
+        assert_eq!("a1", dims.cell_name(0));
+        assert_eq!("e1", dims.cell_name(4));
+        assert_eq!("c4", dims.cell_name(17));
+    }
+    #[test]
+    fn test_cell_index() {
+        let dims = Dimensions::new(5, 5, 4);
+        for index in 0..dims.cells() {
+            assert_eq!(Some(index), dims.cell_index(&dims.cell_name(index)));
+        }
+        assert_eq!(None, dims.cell_index("f1"));
+        assert_eq!(None, dims.cell_index("a0"));
+        assert_eq!(None, dims.cell_index("a"));
+    }
+    #[test]
+    fn test_available_moves_with_gravity() {
+        let dims = Dimensions::default();
+        let mut board = vec![Field::Free; 9];
+        board[0] = Field::Cross;
+        board[3] = Field::Circle;
+        board[6] = Field::Cross;
+        assert_eq!(vec![1, 2], available_moves(&board, &dims, &Rules::gravity()));
+        assert_eq!(vec![1, 2, 4, 5, 7, 8], available_moves(&board, &dims, &Rules::default()));
+        assert_eq!(None, drop_cell(&board, &dims, 0));
+    }
+    #[test]
+    fn test_adjacent() {
+        let dims = Dimensions::default();
+        assert_eq!(vec![2, 6, 0, 8, 7, 1, 5, 3], dims.adjacent(4));
+        assert_eq!(vec![4, 3, 1], dims.adjacent(0));
+        // edge cells are not connected diagonally
+        assert_eq!(vec![4, 2, 0], dims.adjacent(1));
+        assert_eq!(vec![7, 1], Dimensions::new(6, 7, 4).adjacent(0));
+    }
 }
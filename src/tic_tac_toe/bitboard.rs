@@ -0,0 +1,188 @@
+use super::Field;
+
+/// All eight lines of the 3x3 board as bit masks (bit `i` is cell `i`).
+pub const LINE_MASKS: [u16; 8] = [
+    // rows
+    0b000_000_111,
+    0b000_111_000,
+    0b111_000_000,
+    // columns
+    0b001_001_001,
+    0b010_010_010,
+    0b100_100_100,
+    // diagonals
+    0b100_010_001,
+    0b001_010_100,
+];
+
+/// Mask with one bit set for every cell of the board.
+pub const FULL_BOARD: u16 = 0b111_111_111;
+
+/// Compact board representation with one bit mask per player.
+///
+/// Win detection is a handful of mask comparisons instead of walking the `Field` vector, which
+/// makes it suitable for bulk simulations and search.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BitBoard {
+    pub crosses: u16,
+    pub circles: u16,
+}
+
+impl BitBoard {
+    #[must_use]
+    pub fn new() -> BitBoard {
+        BitBoard::default()
+    }
+
+    /// Convert a `Field` board into a bitboard.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the board does not have 9 cells.
+    #[must_use]
+    pub fn from_fields(board: &[Field]) -> BitBoard {
+        assert_eq!(9, board.len(), "a bitboard holds a 3x3 board");
+        let mut bitboard = BitBoard::new();
+        for (i, cell) in board.iter().enumerate() {
+            bitboard.place(i, *cell);
+        }
+        bitboard
+    }
+
+    /// Convert the bitboard back into a `Field` board.
+    #[must_use]
+    pub fn to_fields(self) -> Vec<Field> {
+        (0..9).map(|i| self.get(i)).collect()
+    }
+
+    /// Return the content of a cell.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is not a cell of the 3x3 board.
+    #[must_use]
+    pub fn get(self, index: usize) -> Field {
+        assert!(index < 9, "cell {index} is not on the 3x3 board");
+        let bit = 1 << index;
+        if self.crosses & bit != 0 {
+            Field::Cross
+        }
+        else if self.circles & bit != 0 {
+            Field::Circle
+        }
+        else {
+            Field::Free
+        }
+    }
+
+    /// Set a cell; placing `Field::Free` clears it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is not a cell of the 3x3 board.
+    pub fn place(&mut self, index: usize, player: Field) {
+        assert!(index < 9, "cell {index} is not on the 3x3 board");
+        let bit = 1 << index;
+        self.crosses &= !bit;
+        self.circles &= !bit;
+        match player {
+            Field::Cross => self.crosses |= bit,
+            Field::Circle => self.circles |= bit,
+            Field::Free => {}
+        }
+    }
+
+    /// Bit mask of the cells of a player.
+    #[must_use]
+    pub fn mask(self, player: Field) -> u16 {
+        match player {
+            Field::Cross => self.crosses,
+            Field::Circle => self.circles,
+            Field::Free => self.free(),
+        }
+    }
+
+    /// Bit mask of all free cells.
+    #[must_use]
+    pub fn free(self) -> u16 {
+        !(self.crosses | self.circles) & FULL_BOARD
+    }
+
+    /// Check if the player owns a complete line.
+    #[must_use]
+    pub fn has_won(self, player: Field) -> bool {
+        let mask = self.mask(player);
+        player != Field::Free && LINE_MASKS.iter().any(|line| mask & line == *line)
+    }
+
+    /// Equivalent of `someone_has_won` on the bitboard.
+    #[must_use]
+    pub fn winner(self) -> (bool, Field) {
+        if self.has_won(Field::Cross) {
+            (true, Field::Cross)
+        }
+        else if self.has_won(Field::Circle) {
+            (true, Field::Circle)
+        }
+        else {
+            (false, Field::Free)
+        }
+    }
+
+    /// Equivalent of `cats_game` on the bitboard.
+    #[must_use]
+    pub fn is_full(self) -> bool {
+        self.free() == 0
+    }
+
+    /// Iterate over the indices of all free cells.
+    pub fn available_moves(self) -> impl Iterator<Item = usize> {
+        let mut free = self.free();
+        std::iter::from_fn(move || {
+            if free == 0 {
+                return None;
+            }
+            let index = free.trailing_zeros() as usize;
+            free &= free - 1;
+            Some(index)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let board = vec![Field::Circle, Field::Free, Field::Cross, Field::Free, Field::Cross, Field::Circle, Field::Free, Field::Free, Field::Cross];
+        assert_eq!(board, BitBoard::from_fields(&board).to_fields());
+    }
+    #[test]
+    fn test_winner_diagonal() {
+        let board = vec![Field::Free, Field::Free, Field::Circle, Field::Free, Field::Circle, Field::Free, Field::Circle, Field::Free, Field::Free];
+        assert_eq!((true, Field::Circle), BitBoard::from_fields(&board).winner());
+    }
+    #[test]
+    fn test_no_winner_for_mixed_line() {
+        let board = vec![Field::Cross, Field::Circle, Field::Cross, Field::Free, Field::Free, Field::Free, Field::Free, Field::Free, Field::Free];
+        assert_eq!((false, Field::Free), BitBoard::from_fields(&board).winner());
+    }
+    #[test]
+    fn test_available_moves() {
+        let board = vec![Field::Cross, Field::Free, Field::Circle, Field::Free, Field::Cross, Field::Circle, Field::Circle, Field::Cross, Field::Free];
+        let bitboard = BitBoard::from_fields(&board);
+        assert_eq!(vec![1, 3, 8], bitboard.available_moves().collect::<Vec<usize>>());
+        assert!(!bitboard.is_full());
+    }
+    #[test]
+    #[should_panic(expected = "a bitboard holds a 3x3 board")]
+    fn test_from_fields_rejects_other_sizes() {
+        let _ = BitBoard::from_fields(&[Field::Free; 16]);
+    }
+    #[test]
+    #[should_panic(expected = "cell 9 is not on the 3x3 board")]
+    fn test_place_rejects_cells_off_the_board() {
+        BitBoard::new().place(9, Field::Cross);
+    }
+}
@@ -0,0 +1,339 @@
+use std::thread;
+use std::time::Duration;
+extern crate rand;
+use rand::Rng;
+use fltk::{app, enums::{Color, Cursor, Shortcut}, menu::{MenuBar, MenuFlag}, prelude::*, window::Window};
+use crate::gui::*;
+use super::super::{Field, Mode, VariantMessage};
+use super::super::session::{IllegalMove, Outcome, Score};
+use super::super::variant::{MessageTarget, Variant};
+use super::{check_radio_item, DIFFICULTIES, MENU_HEIGHT};
+
+/// Seconds the AI may take beyond its think time before it plays a random move.
+const GRACE_TIME: f64 = 0.5;
+
+/// A window of its own that is kept open next to the main window.
+pub(super) trait VariantWindow: MessageTarget {
+    /// Whether the window is still open. A closed window is deleted.
+    fn is_open(&mut self) -> bool;
+}
+
+/// A game of a variant `G` shown by the view `V`, played against the AI or another player at
+/// the same computer.
+pub(super) struct VariantTable<G: Variant, V: VariantView<G>> {
+    wind: Window,
+    menu: MenuBar,
+    view: V,
+    status: StatusPanel,
+    mode_items: Vec<i32>,
+    difficulty_items: Vec<i32>,
+    sender: VariantSender,
+    game: G,
+    score: Score,
+    mode: Mode,
+    /// Side the AI plays against the player, `Field::Cross` moves first.
+    ai_side: Field,
+    difficulty: i32,
+    /// Seconds the AI may think about a move.
+    think_time: f64,
+    /// Number of the request the AI is thinking about, see `Table`.
+    thinking: Option<u64>,
+    next_request: u64,
+}
+
+/// Stack the menu bar, the board and the status panel below each other.
+fn layout_window<G: Variant, V: VariantView<G>>(menu: &mut MenuBar, view: &mut V, status: &mut StatusPanel, w: i32, h: i32) {
+    let status_h = (h - MENU_HEIGHT) / 6;
+    menu.resize(0, 0, w, MENU_HEIGHT);
+    view.resize(0, MENU_HEIGHT, w, h - MENU_HEIGHT - status_h);
+    status.resize(0, h - status_h, w, status_h);
+}
+
+impl<G: Variant, V: VariantView<G>> VariantTable<G, V> {
+    /// Open a window for `game` with the settings of the main window, whose messages are sent
+    /// through `sender`.
+    pub(super) fn open(game: G, theme: &Theme, difficulty: i32, think_time: f64, sender: VariantSender) -> VariantTable<G, V> {
+        let (_, _, work_w, work_h) = app::screen_work_area(0);
+        // the board gets two thirds of the screen height, but has to fit the width
+        let board_h = f64::from(work_h * 2 / 3).min(f64::from(work_w * 9 / 10) / V::ASPECT).max(300.0 / V::ASPECT);
+        let (win_w, board_h) = ((board_h * V::ASPECT) as i32, board_h as i32);
+        let win_h = MENU_HEIGHT + board_h * 6 / 5;
+        let mut wind = Window::default().with_size(win_w, win_h).center_screen().with_label(&game.name());
+        let menu = MenuBar::new(0, 0, win_w, MENU_HEIGHT, "");
+        let view = V::new(0, MENU_HEIGHT, win_w, board_h, &game, sender);
+        let status = StatusPanel::new(0, MENU_HEIGHT + board_h, win_w, win_h - MENU_HEIGHT - board_h);
+        wind.make_resizable(true);
+        wind.size_range(200, 180 + MENU_HEIGHT, 0, 0);
+        wind.end();
+        let mut table = VariantTable {
+            wind,
+            menu,
+            view,
+            status,
+            mode_items: vec![],
+            difficulty_items: vec![],
+            sender,
+            game,
+            score: Score::default(),
+            mode: Mode::VersusAi,
+            ai_side: Field::Circle,
+            difficulty,
+            think_time,
+            thinking: None,
+            next_request: 0,
+        };
+        table.build_menu();
+        let mut menu = table.menu.clone();
+        let mut view = table.view.clone();
+        let mut status = table.status.clone();
+        table.wind.resize_callback(move |_, _, _, w, h| layout_window::<G, V>(&mut menu, &mut view, &mut status, w, h));
+        table.wind.set_color(Color::from_hex(theme.window));
+        table.view.set_theme(theme);
+        table.status.set_theme(theme);
+        check_radio_item(&table.menu, &table.mode_items, 0);
+        check_radio_item(&table.menu, &table.difficulty_items, difficulty as usize);
+        table.wind.show();
+        table.new_game();
+        table.update_status();
+        table
+    }
+
+    fn build_menu(&mut self) {
+        let s = self.sender;
+        let menu = &mut self.menu;
+        menu.add_emit("Game/New", Shortcut::Ctrl | 'n', MenuFlag::Normal, s.sender(), s.message(VariantMessage::NewGame));
+        menu.add_emit("Game/Undo", Shortcut::Ctrl | 'z', MenuFlag::MenuDivider, s.sender(), s.message(VariantMessage::Undo));
+        menu.add_emit("Game/Close", Shortcut::Ctrl | 'w', MenuFlag::Normal, s.sender(), s.message(VariantMessage::Close));
+        let modes = [
+            ("Versus AI", VariantMessage::SetAiSide(Field::Circle)),
+            ("Versus AI, AI starts", VariantMessage::SetAiSide(Field::Cross)),
+            ("Hotseat", VariantMessage::SetMode(Mode::Hotseat)),
+        ];
+        for (name, message) in modes {
+            let item = menu.add_emit(&format!("Mode/{name}"), Shortcut::None, MenuFlag::Radio, s.sender(), s.message(message));
+            self.mode_items.push(item);
+        }
+        for (difficulty, name) in DIFFICULTIES.iter().enumerate() {
+            let message = s.message(VariantMessage::SetDifficulty(difficulty as i32));
+            let item = menu.add_emit(&format!("Difficulty/{name}"), Shortcut::None, MenuFlag::Radio, s.sender(), message);
+            self.difficulty_items.push(item);
+        }
+    }
+
+    /// Whether the AI plays the marks of `player`.
+    fn is_ai(&self, player: Field) -> bool {
+        self.mode == Mode::VersusAi && player == self.ai_side
+    }
+
+    /// Name of the side playing `player` in the status panel.
+    fn player_name(&self, player: Field) -> String {
+        match self.mode {
+            Mode::VersusAi if self.is_ai(player) => "AI".to_string(),
+            Mode::VersusAi => "You".to_string(),
+            _ => self.game.player_name(player),
+        }
+    }
+
+    /// Show the state of the game and the score in the status panel.
+    fn update_status(&mut self) {
+        let game = &self.game;
+        let turn = match game.outcome() {
+            Some(Outcome::Won(player)) if self.mode == Mode::VersusAi && !self.is_ai(player) => "You have won!".to_string(),
+            Some(Outcome::Won(player)) => format!("{} has won!", self.player_name(player)),
+            Some(Outcome::Draw) => "Cats game! Remis!".to_string(),
+            None => {
+                let player = game.to_move();
+                let turn = if self.mode == Mode::VersusAi && !self.is_ai(player) {
+                    format!("Your turn ({})", game.player_name(player))
+                }
+                else if self.thinking.is_some() {
+                    format!("{} is thinking...", self.player_name(player))
+                }
+                else {
+                    format!("{} to move", self.player_name(player))
+                };
+                let difficulty = match self.mode {
+                    Mode::VersusAi => format!("   Difficulty: {}", DIFFICULTIES[self.difficulty as usize]),
+                    _ => String::new(),
+                };
+                let last_move = match game.history().last() {
+                    Some(mv) => game.move_name(*mv),
+                    None => "-".to_string(),
+                };
+                format!("{turn}{difficulty}   Last move: {last_move}")
+            }
+        };
+        let score = format!(
+            "Score   {}: {}   {}: {}   Cats games: {}",
+            self.player_name(Field::Cross),
+            self.score.crosses,
+            self.player_name(Field::Circle),
+            self.score.circles,
+            self.score.draws
+        );
+        self.status.set_turn(&turn);
+        self.status.set_score(&score);
+    }
+
+    /// Show the game on the board, animating the move `last`.
+    fn show(&mut self, last: Option<usize>) {
+        let player = self.game.to_move();
+        let ghost = if self.game.outcome().is_none() && !self.is_ai(player) { player } else { Field::Free };
+        self.view.show(&self.game, last, ghost);
+    }
+
+    fn new_game(&mut self) {
+        self.game = self.game.restart();
+        self.stop_thinking();
+        self.status.set_message("");
+        self.show(None);
+        self.ai_step();
+    }
+
+    /// Show a move that was just made and continue with the next player.
+    fn after_move(&mut self, mv: usize) {
+        self.show(Some(mv));
+        if let Some(outcome) = self.game.outcome() {
+            self.score.record(outcome);
+            self.status.set_message("Press Ctrl+N for a new game.");
+        }
+        self.ai_step();
+    }
+
+    /// Handle a click on a cell of the board.
+    fn play_cell(&mut self, cell: usize) {
+        if self.game.outcome().is_none() && self.is_ai(self.game.to_move()) {
+            if self.thinking.is_some() {
+                self.status.set_message("Please wait, the AI is thinking.");
+            }
+            return;
+        }
+        let Some(mv) = self.view.click(&self.game, cell) else {
+            return;
+        };
+        match self.game.play(mv) {
+            Err(err @ (IllegalMove::Occupied(_) | IllegalMove::WrongBoard | IllegalMove::Floating | IllegalMove::AllPlaced | IllegalMove::NotAdjacent | IllegalMove::StillPlacing | IllegalMove::NotYourMark | IllegalMove::DeadBoard | IllegalMove::NumberUnavailable)) => {
+                self.status.set_message(&format!("{err}."));
+            }
+            Err(IllegalMove::GameOver) => self.status.set_message("The game is over. Press Ctrl+N for a new game."),
+            Err(IllegalMove::OutOfBoard) => {}
+            Ok(()) => {
+                self.status.set_message("");
+                self.after_move(mv);
+            }
+        }
+    }
+
+    /// Let the AI think about its move on a background thread if it is its turn.
+    fn ai_step(&mut self) {
+        if self.game.outcome().is_some() || !self.is_ai(self.game.to_move()) || self.thinking.is_some() {
+            return;
+        }
+        let request = self.next_request;
+        self.next_request += 1;
+        self.thinking = Some(request);
+        self.wind.set_cursor(Cursor::Wait);
+        let s = self.sender;
+        let game = self.game.clone();
+        let (difficulty, think_time) = (self.difficulty, self.think_time);
+        thread::spawn(move || {
+            if let Some(mv) = game.ai_move(difficulty, Duration::from_secs_f64(think_time)) {
+                s.send(VariantMessage::AiMove(request, mv));
+            }
+        });
+        app::add_timeout3(self.think_time + GRACE_TIME, move |_| s.send(VariantMessage::AiTimeout(request)));
+    }
+
+    /// Forget the request the AI is thinking about, its answer will be ignored.
+    fn stop_thinking(&mut self) {
+        if self.thinking.take().is_some() {
+            self.wind.set_cursor(Cursor::Default);
+        }
+    }
+
+    /// Play the move the AI chose for `request`, unless the request is outdated.
+    fn finish_ai_move(&mut self, request: u64, mv: Option<usize>) {
+        if self.thinking != Some(request) {
+            return;
+        }
+        self.stop_thinking();
+        let mv = match mv {
+            Some(mv) => mv,
+            None => {
+                self.status.set_message("The AI ran out of time and played a random move.");
+                let moves = self.game.legal_moves();
+                if moves.is_empty() {
+                    return;
+                }
+                moves[rand::thread_rng().gen_range(0..moves.len())]
+            }
+        };
+        if self.game.play(mv).is_ok() {
+            self.after_move(mv);
+        }
+    }
+
+    /// Take back the last move of the player and, against the AI, the AI's answer.
+    fn undo(&mut self) {
+        if self.thinking.is_some() {
+            self.status.set_message("Please wait, the AI is thinking.");
+            return;
+        }
+        if self.game.outcome().is_some() {
+            self.status.set_message("The game is over. Press Ctrl+N for a new game.");
+            return;
+        }
+        while self.game.undo().is_some() {
+            if !self.is_ai(self.game.to_move()) {
+                break;
+            }
+        }
+        self.status.set_message("");
+        self.show(None);
+        // without a move of the player left, the AI starts again
+        self.ai_step();
+    }
+
+}
+
+impl<G: Variant, V: VariantView<G>> MessageTarget for VariantTable<G, V> {
+    fn handle(&mut self, message: VariantMessage) {
+        match message {
+            VariantMessage::Number(cell) => self.play_cell(cell),
+            VariantMessage::AiMove(request, mv) => self.finish_ai_move(request, Some(mv)),
+            VariantMessage::AiTimeout(request) => self.finish_ai_move(request, None),
+            VariantMessage::NewGame => self.new_game(),
+            VariantMessage::Undo => self.undo(),
+            VariantMessage::Close => self.wind.hide(),
+            VariantMessage::SetMode(mode) => {
+                self.mode = mode;
+                self.score = Score::default();
+                check_radio_item(&self.menu, &self.mode_items, self.mode_items.len() - 1);
+                self.new_game();
+            }
+            VariantMessage::SetAiSide(side) => {
+                self.mode = Mode::VersusAi;
+                self.ai_side = side;
+                self.score = Score::default();
+                check_radio_item(&self.menu, &self.mode_items, usize::from(side == Field::Cross));
+                self.new_game();
+            }
+            VariantMessage::SetDifficulty(difficulty) => {
+                self.difficulty = difficulty;
+                check_radio_item(&self.menu, &self.difficulty_items, difficulty as usize);
+            }
+        }
+        self.update_status();
+    }
+}
+
+impl<G: Variant, V: VariantView<G>> VariantWindow for VariantTable<G, V> {
+    fn is_open(&mut self) -> bool {
+        if self.wind.shown() {
+            return true;
+        }
+        self.stop_thinking();
+        app::delete_widget(self.wind.clone());
+        false
+    }
+}
@@ -0,0 +1,366 @@
+use std::sync::OnceLock;
+use super::{Dimensions, Field};
+use super::session::{IllegalMove, Outcome};
+use super::solver::opponent;
+use super::variant::{Variant, WIN};
+
+/// Number of rows and columns of the board.
+pub const SIZE: usize = 15;
+/// Number of marks in a row that win.
+pub const FIVE: usize = 5;
+
+/// Steps to the next cell of a line, in rows and columns.
+const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+/// Value of the lines of five cells for the search, by the number of marks of a single player
+/// in them.
+const LINE_VALUES: [i32; FIVE] = [0, 1, 4, 16, 64];
+/// Values of the threats of `Patterns`.
+const FOUR: i32 = 400;
+const OPEN_FOUR: i32 = 5000;
+const OPEN_THREE: i32 = 300;
+/// Number of the most promising moves the search looks at in every position.
+const MAX_CANDIDATES: usize = 12;
+/// Distance to the nearest mark of the cells the search looks at.
+const NEIGHBOURHOOD: isize = 2;
+
+/// Whether more than five marks in a row win.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GomokuRule {
+    /// Five or more in a row win.
+    Freestyle,
+    /// Exactly five in a row win, longer lines (overlines) do not count.
+    ExactFive,
+}
+
+/// Numbers of the threats of a player that the AI looks for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Patterns {
+    /// Lines of five cells with four marks that are completed by the last free cell.
+    pub fours: usize,
+    /// Four marks in a row with free cells at both ends, which cannot be stopped anymore.
+    pub open_fours: usize,
+    /// Three marks within four cells with free cells at both ends, which become an open four
+    /// unless they are blocked.
+    pub open_threes: usize,
+}
+
+/// Lines the threats are looked for in.
+struct Windows {
+    fives: Vec<Vec<usize>>,
+    sixes: Vec<Vec<usize>>,
+    /// Indices in `fives` of the lines through every cell.
+    by_cell: Vec<Vec<usize>>,
+}
+
+/// Gomoku: five in a row on a 15x15 board.
+///
+/// Move `m` takes cell `m`, the cells are numbered like those of other boards (row by row
+/// from the bottom left).
+#[derive(Clone, Debug)]
+pub struct Gomoku {
+    rule: GomokuRule,
+    board: Vec<Field>,
+    to_move: Field,
+    history: Vec<usize>,
+    /// Cells of the line that won the game.
+    won_line: Option<Vec<usize>>,
+}
+
+impl Gomoku {
+    #[must_use]
+    pub fn new(rule: GomokuRule) -> Gomoku {
+        Gomoku { rule, board: vec![Field::Free; SIZE * SIZE], to_move: Field::Cross, history: vec![], won_line: None }
+    }
+
+    #[must_use]
+    pub fn dims() -> Dimensions {
+        Dimensions::new(SIZE, SIZE, FIVE)
+    }
+
+    #[must_use]
+    pub fn rule(&self) -> GomokuRule {
+        self.rule
+    }
+
+    #[must_use]
+    pub fn board(&self) -> &[Field] {
+        &self.board
+    }
+
+    /// Return the cells of the line that won the game, if there is one.
+    #[must_use]
+    pub fn winning_line(&self) -> Option<&[usize]> {
+        self.won_line.as_deref()
+    }
+
+    /// Lines of five and of six cells on the board, computed once.
+    fn windows() -> &'static Windows {
+        static WINDOWS: OnceLock<Windows> = OnceLock::new();
+        WINDOWS.get_or_init(|| {
+            let fives = Gomoku::dims().lines();
+            let by_cell = (0..SIZE * SIZE).map(|cell| (0..fives.len()).filter(|window| fives[*window].contains(&cell)).collect()).collect();
+            Windows { fives, sixes: Dimensions::new(SIZE, SIZE, FIVE + 1).lines(), by_cell }
+        })
+    }
+
+    /// Return the cells of the row of marks of `player` through `cell` in the given direction,
+    /// counting `cell` as one of them.
+    fn run(&self, cell: usize, player: Field, (d_row, d_col): (isize, isize)) -> Vec<usize> {
+        let dims = Gomoku::dims();
+        let mut run = vec![cell];
+        for sign in [-1, 1] {
+            let mut current = cell;
+            while let Some(next) = dims.neighbour(current, sign * d_row, sign * d_col) {
+                if self.board[next] != player {
+                    break;
+                }
+                run.push(next);
+                current = next;
+            }
+        }
+        run.sort_unstable();
+        run
+    }
+
+    /// Return the line that a mark of `player` on `cell` would win with. Only the rows through
+    /// `cell` are checked, so this is all that is needed after each move.
+    fn five_at(&self, cell: usize, player: Field) -> Option<Vec<usize>> {
+        DIRECTIONS.iter().map(|direction| self.run(cell, player, *direction)).find(|run| match self.rule {
+            GomokuRule::Freestyle => run.len() >= FIVE,
+            GomokuRule::ExactFive => run.len() == FIVE,
+        })
+    }
+
+    /// Count the threats of `player` on the board. A threat is told apart by its marks, so
+    /// three marks that fit into two windows, like `_XXX__` and `__XXX_`, count once.
+    #[must_use]
+    pub fn patterns(&self, player: Field) -> Patterns {
+        let Windows { fives, sixes, .. } = Gomoku::windows();
+        let marks = |cells: &[usize]| -> Vec<usize> { cells.iter().copied().filter(|cell| self.board[*cell] == player).collect() };
+        let mut fours = vec![];
+        let mut open_fours = vec![];
+        let mut open_threes = vec![];
+        for window in fives {
+            let own = marks(window);
+            let free = window.iter().filter(|cell| self.board[**cell] == Field::Free).count();
+            if own.len() == FIVE - 1 && free == 1 {
+                fours.push(own);
+            }
+        }
+        for window in sixes {
+            if self.board[window[0]] != Field::Free || self.board[window[FIVE]] != Field::Free {
+                continue;
+            }
+            let inner = &window[1..FIVE];
+            let own = marks(inner);
+            let free = inner.iter().filter(|cell| self.board[**cell] == Field::Free).count();
+            match (own.len(), free) {
+                (4, 0) => open_fours.push(own),
+                (3, 1) => open_threes.push(own),
+                _ => {}
+            }
+        }
+        let count = |mut threats: Vec<Vec<usize>>| {
+            threats.sort_unstable();
+            threats.dedup();
+            threats.len()
+        };
+        Patterns { fours: count(fours), open_fours: count(open_fours), open_threes: count(open_threes) }
+    }
+
+    /// Heuristic value of the board for `player` alone.
+    fn score(&self, player: Field) -> i32 {
+        let patterns = self.patterns(player);
+        let lines: i32 = Gomoku::windows()
+            .fives
+            .iter()
+            .map(|window| {
+                let own = window.iter().filter(|cell| self.board[**cell] == player).count();
+                let other = window.iter().any(|cell| self.board[*cell] == opponent(player));
+                if other || own >= FIVE { 0 } else { LINE_VALUES[own] }
+            })
+            .sum();
+        lines + patterns.fours as i32 * FOUR + patterns.open_fours as i32 * OPEN_FOUR + patterns.open_threes as i32 * OPEN_THREE
+    }
+
+    /// Value of a free cell for move ordering: cells on lines with many marks of a single
+    /// player come first, for attack and defence.
+    fn cell_value(&self, cell: usize) -> i32 {
+        let windows = Gomoku::windows();
+        windows.by_cell[cell]
+            .iter()
+            .map(|window| {
+                let crosses = windows.fives[*window].iter().filter(|cell| self.board[**cell] == Field::Cross).count();
+                let circles = windows.fives[*window].iter().filter(|cell| self.board[**cell] == Field::Circle).count();
+                match (crosses, circles) {
+                    (marks, 0) | (0, marks) if marks < FIVE => LINE_VALUES[marks] + 1,
+                    _ => 0,
+                }
+            })
+            .sum()
+    }
+
+    /// Free cells close to a mark. On the empty board only the center.
+    fn nearby_cells(&self) -> Vec<usize> {
+        let dims = Gomoku::dims();
+        if self.history.is_empty() {
+            return vec![dims.center()];
+        }
+        (0..SIZE * SIZE)
+            .filter(|cell| self.board[*cell] == Field::Free)
+            .filter(|cell| {
+                (-NEIGHBOURHOOD..=NEIGHBOURHOOD).any(|d_row| {
+                    (-NEIGHBOURHOOD..=NEIGHBOURHOOD).any(|d_col| dims.neighbour(*cell, d_row, d_col).is_some_and(|near| self.board[near] != Field::Free))
+                })
+            })
+            .collect()
+    }
+}
+
+impl Variant for Gomoku {
+    fn name(&self) -> String {
+        match self.rule {
+            GomokuRule::Freestyle => "Gomoku".to_string(),
+            GomokuRule::ExactFive => "Gomoku (exactly five)".to_string(),
+        }
+    }
+
+    fn restart(&self) -> Self {
+        Gomoku::new(self.rule)
+    }
+
+    fn to_move(&self) -> Field {
+        self.to_move
+    }
+
+    fn history(&self) -> &[usize] {
+        &self.history
+    }
+
+    fn legal_moves(&self) -> Vec<usize> {
+        if self.outcome().is_some() {
+            return vec![];
+        }
+        (0..SIZE * SIZE).filter(|cell| self.board[*cell] == Field::Free).collect()
+    }
+
+    fn play(&mut self, mv: usize) -> Result<(), IllegalMove> {
+        if self.outcome().is_some() {
+            return Err(IllegalMove::GameOver);
+        }
+        if mv >= SIZE * SIZE {
+            return Err(IllegalMove::OutOfBoard);
+        }
+        if self.board[mv] != Field::Free {
+            return Err(IllegalMove::Occupied(self.board[mv]));
+        }
+        self.won_line = self.five_at(mv, self.to_move);
+        self.board[mv] = self.to_move;
+        self.history.push(mv);
+        self.to_move = opponent(self.to_move);
+        Ok(())
+    }
+
+    fn undo(&mut self) -> Option<usize> {
+        let mv = self.history.pop()?;
+        self.to_move = self.board[mv];
+        self.board[mv] = Field::Free;
+        self.won_line = None;
+        Some(mv)
+    }
+
+    fn outcome(&self) -> Option<Outcome> {
+        match &self.won_line {
+            Some(line) => Some(Outcome::Won(self.board[line[0]])),
+            None if self.history.len() == SIZE * SIZE => Some(Outcome::Draw),
+            None => None,
+        }
+    }
+
+    fn move_name(&self, mv: usize) -> String {
+        Gomoku::dims().cell_name(mv)
+    }
+
+    fn evaluate(&self, player: Field) -> i32 {
+        let (own, theirs) = (self.score(player), self.score(opponent(player)));
+        // the player to move gets to use their threats first
+        let value = if self.to_move == player { own * 3 / 2 - theirs } else { own - theirs * 3 / 2 };
+        value.clamp(-WIN / 2, WIN / 2)
+    }
+
+    /// Forced moves only: five can be completed or five of the opponent have to be blocked.
+    /// Otherwise the most promising cells near the marks on the board.
+    fn candidate_moves(&self) -> Vec<usize> {
+        if self.outcome().is_some() {
+            return vec![];
+        }
+        let mut moves = self.nearby_cells();
+        for player in [self.to_move, opponent(self.to_move)] {
+            let forced: Vec<usize> = moves.iter().copied().filter(|cell| self.five_at(*cell, player).is_some()).collect();
+            if !forced.is_empty() {
+                return forced;
+            }
+        }
+        moves.sort_by_cached_key(|cell| -self.cell_value(*cell));
+        moves.truncate(MAX_CANDIDATES);
+        moves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Play the moves of both players alternately, starting with cross.
+    fn game_with(rule: GomokuRule, crosses: &[usize], circles: &[usize]) -> Gomoku {
+        let mut game = Gomoku::new(rule);
+        for (cross, circle) in crosses.iter().zip(circles) {
+            game.play(*cross).unwrap();
+            game.play(*circle).unwrap();
+        }
+        game
+    }
+    #[test]
+    fn test_five_wins() {
+        let mut game = game_with(GomokuRule::Freestyle, &[0, 16, 32, 48], &[1, 2, 3, 4]);
+        assert_eq!(None, game.outcome());
+        game.play(64).unwrap();
+        assert_eq!(Some(Outcome::Won(Field::Cross)), game.outcome());
+        assert_eq!(Some(&[0, 16, 32, 48, 64][..]), game.winning_line());
+    }
+    #[test]
+    fn test_overline() {
+        // cross has 0-2 and 4-5 in the first row, 3 makes six in a row
+        let crosses = [0, 1, 2, 4, 5];
+        let circles = [30, 31, 32, 33, 224];
+        let mut game = game_with(GomokuRule::ExactFive, &crosses, &circles);
+        game.play(3).unwrap();
+        assert_eq!(None, game.outcome());
+        let mut game = game_with(GomokuRule::Freestyle, &crosses, &circles);
+        game.play(3).unwrap();
+        assert_eq!(Some(Outcome::Won(Field::Cross)), game.outcome());
+        assert_eq!(Some(&[0, 1, 2, 3, 4, 5][..]), game.winning_line());
+    }
+    #[test]
+    fn test_patterns() {
+        let game = game_with(GomokuRule::Freestyle, &[112, 113, 114], &[0, 14, 210]);
+        assert_eq!(Patterns { fours: 0, open_fours: 0, open_threes: 1 }, game.patterns(Field::Cross));
+        // the open four is also a four from either end
+        let game = game_with(GomokuRule::Freestyle, &[112, 113, 114, 115], &[0, 14, 210, 224]);
+        assert_eq!(Patterns { fours: 1, open_fours: 1, open_threes: 0 }, game.patterns(Field::Cross));
+        assert_eq!(Patterns::default(), game.patterns(Field::Circle));
+    }
+    #[test]
+    fn test_ai_blocks_four() {
+        let mut game = game_with(GomokuRule::Freestyle, &[0, 100, 200], &[112, 113, 114]);
+        game.play(224).unwrap();
+        game.play(115).unwrap();
+        // circle has an open four and wins on either side, cross can only block one
+        assert_eq!(2, game.candidate_moves().len());
+        game.play(game.ai_move(2, Duration::from_millis(200)).unwrap()).unwrap();
+        let win = game.ai_move(2, Duration::from_millis(200)).unwrap();
+        game.play(win).unwrap();
+        assert_eq!(Some(Outcome::Won(Field::Circle)), game.outcome());
+    }
+}
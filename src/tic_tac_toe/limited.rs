@@ -0,0 +1,144 @@
+use std::time::Duration;
+use super::Field;
+use super::session::{Game, IllegalMove, Outcome};
+use super::solver::opponent;
+use super::variant::{self, Variant};
+
+/// Choose a move for the player to move when every player only keeps a limited number of
+/// marks (see `Rules::limited`).
+///
+/// The game never ends in a draw, so the search (see `variant::search_move`) looks for
+/// repetitions instead: the marks on the board in the order they vanish are the whole position,
+/// and the defending side can repeat it forever.
+///
+/// # Panics
+///
+/// Panics if the game is already over.
+#[must_use]
+pub fn search_move(game: &Game, difficulty: i32, think_time: Duration) -> usize {
+    variant::search_move(&Limited(game.clone()), difficulty, think_time).expect("There is no move to make")
+}
+
+/// A game with limited marks, searched like the variants.
+#[derive(Clone)]
+struct Limited(Game);
+
+impl Variant for Limited {
+    fn name(&self) -> String {
+        "Tic-tac-toe with limited marks".to_string()
+    }
+
+    fn restart(&self) -> Self {
+        Limited(Game::with_rules(*self.0.dims(), *self.0.rules()))
+    }
+
+    fn to_move(&self) -> Field {
+        self.0.to_move()
+    }
+
+    fn history(&self) -> &[usize] {
+        self.0.history()
+    }
+
+    fn legal_moves(&self) -> Vec<usize> {
+        if self.0.outcome().is_some() {
+            return vec![];
+        }
+        (0..self.0.board().len()).filter(|cell| self.0.board()[*cell] == Field::Free).collect()
+    }
+
+    fn play(&mut self, mv: usize) -> Result<(), IllegalMove> {
+        self.0.play(mv)
+    }
+
+    fn undo(&mut self) -> Option<usize> {
+        self.0.undo()
+    }
+
+    fn outcome(&self) -> Option<Outcome> {
+        self.0.outcome()
+    }
+
+    fn move_name(&self, mv: usize) -> String {
+        self.0.dims().cell_name(mv)
+    }
+
+    fn evaluate(&self, player: Field) -> i32 {
+        let value = evaluate(&self.0);
+        if player == self.0.to_move() { value } else { -value }
+    }
+
+    /// The player to move and the last moves, which are exactly the marks on the board in the
+    /// order they vanish.
+    fn position_key(&self) -> Option<Vec<usize>> {
+        let history = self.0.history();
+        let marks = 2 * self.0.dims().in_a_row;
+        let mut key = vec![usize::from(self.0.to_move() == Field::Cross)];
+        key.extend_from_slice(&history[history.len().saturating_sub(marks)..]);
+        Some(key)
+    }
+}
+
+/// Heuristic value for the player to move: lines with marks of only one player count, but
+/// not the marks that vanish with the next move of their owner. With misère rules the lines
+/// count against their owner.
+fn evaluate(game: &Game) -> i32 {
+    let player = game.to_move();
+    let board = game.board();
+    let staying = |cell: usize, owner: Field| board[cell] == owner && game.next_to_vanish(owner) != Some(cell);
+    let lines: i32 = game
+        .dims()
+        .lines()
+        .iter()
+        .map(|line| {
+            let own = line.iter().filter(|cell| staying(**cell, player)).count() as i32;
+            let theirs = line.iter().filter(|cell| staying(**cell, opponent(player))).count() as i32;
+            let blocked = |owner: Field| line.iter().any(|cell| board[*cell] == owner);
+            match (blocked(opponent(player)), blocked(player)) {
+                (false, _) if own > 0 => own * own,
+                (_, false) if theirs > 0 => -theirs * theirs,
+                _ => 0,
+            }
+        })
+        .sum();
+    if game.rules().misere { -lines } else { lines }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{Dimensions, Rules};
+
+    fn limited_game(moves: &[usize]) -> Game {
+        let mut game = Game::with_rules(Dimensions::default(), Rules::limited());
+        for cell in moves {
+            game.play(*cell).unwrap();
+        }
+        game
+    }
+    #[test]
+    fn test_completes_line() {
+        // the oldest cross on 4 vanishes with the next move, the crosses on 0 and 1 stay and
+        // 2 completes their line
+        let game = limited_game(&[4, 3, 0, 5, 1, 8]);
+        assert_eq!(Some(4), game.next_to_vanish(Field::Cross));
+        assert_eq!(2, search_move(&game, 2, Duration::from_millis(200)));
+    }
+    #[test]
+    fn test_blocks_line() {
+        // circle has 3 and 4 and cross has to block 5
+        let game = limited_game(&[0, 3, 8, 4]);
+        assert_eq!(5, search_move(&game, 2, Duration::from_millis(200)));
+    }
+    #[test]
+    fn test_misere_lines_count_against_their_owner() {
+        // the cross in the center has more open lines than the circle in the corner
+        let game = limited_game(&[4, 0]);
+        assert!(evaluate(&game) > 0);
+        let mut misere = Game::with_rules(Dimensions::default(), Rules { misere: true, ..Rules::limited() });
+        for cell in [4, 0] {
+            misere.play(cell).unwrap();
+        }
+        assert_eq!(-evaluate(&game), evaluate(&misere));
+    }
+}
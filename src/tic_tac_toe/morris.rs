@@ -0,0 +1,274 @@
+use super::{Dimensions, Field};
+use super::check_functions::winning_line;
+use super::session::{IllegalMove, Outcome};
+use super::solver::opponent;
+use super::variant::{Variant, WIN};
+
+/// Number of marks every player places before the marks are moved.
+pub const MARKS: usize = 3;
+/// Number of cells of the board.
+pub const CELLS: usize = 9;
+
+/// Number of times a position has to occur for a draw.
+const REPETITIONS: usize = 3;
+/// Value of the lines for the search, by the number of marks of a single player in them.
+const LINE_VALUES: [i32; MARKS] = [0, 1, 10];
+/// Value of every move a player could make.
+const MOBILITY_VALUE: i32 = 2;
+
+/// Three Men's Morris on the 3x3 board.
+///
+/// The players first place their three marks like in tic-tac-toe. After that, every move
+/// slides a mark of the player to a free cell connected to it (see `Dimensions::adjacent`).
+/// Whoever gets three marks in a line wins, a player who cannot move loses, and the game is
+/// drawn when the same position occurs for the third time.
+///
+/// Move `m` below `CELLS` places a mark on cell `m`, all other moves are slides made by
+/// `slide`.
+#[derive(Clone, Debug)]
+pub struct Morris {
+    board: Vec<Field>,
+    to_move: Field,
+    history: Vec<usize>,
+    /// Key of the position before the first and after every move, to find repetitions.
+    positions: Vec<u32>,
+}
+
+impl Default for Morris {
+    fn default() -> Self {
+        Morris::new()
+    }
+}
+
+/// Return the move sliding the mark on `from` to `to`.
+#[must_use]
+pub fn slide(from: usize, to: usize) -> usize {
+    CELLS + from * CELLS + to
+}
+
+/// Return the cell a move takes the mark from (`None` when it is placed) and the cell it
+/// takes the mark to.
+#[must_use]
+pub fn move_cells(mv: usize) -> (Option<usize>, usize) {
+    if mv < CELLS {
+        (None, mv)
+    }
+    else {
+        (Some((mv - CELLS) / CELLS), (mv - CELLS) % CELLS)
+    }
+}
+
+impl Morris {
+    #[must_use]
+    pub fn new() -> Morris {
+        let mut game = Morris { board: vec![Field::Free; CELLS], to_move: Field::Cross, history: vec![], positions: vec![] };
+        game.positions.push(game.position_key());
+        game
+    }
+
+    #[must_use]
+    pub fn board(&self) -> &[Field] {
+        &self.board
+    }
+
+    /// Whether the marks are still being placed.
+    #[must_use]
+    pub fn placing(&self) -> bool {
+        self.history.len() < 2 * MARKS
+    }
+
+    /// Return the cells of the line that won the game, if there is one.
+    #[must_use]
+    pub fn winning_line(&self) -> Option<Vec<usize>> {
+        winning_line(&self.board, &Dimensions::default())
+    }
+
+    /// The board and the player to move as a single number.
+    fn position_key(&self) -> u32 {
+        let board = self.board.iter().rev().fold(0, |key, cell| {
+            key * 3
+                + match cell {
+                    Field::Free => 0,
+                    Field::Cross => 1,
+                    Field::Circle => 2,
+                }
+        });
+        board * 2 + u32::from(self.to_move == Field::Circle)
+    }
+
+    /// Return the moves of the player to move, regardless of whether the game is over.
+    fn moves(&self) -> Vec<usize> {
+        let free = (0..CELLS).filter(|cell| self.board[*cell] == Field::Free);
+        if self.placing() {
+            return free.collect();
+        }
+        let dims = Dimensions::default();
+        (0..CELLS)
+            .filter(|from| self.board[*from] == self.to_move)
+            .flat_map(|from| dims.adjacent(from).into_iter().filter(|to| self.board[*to] == Field::Free).map(move |to| slide(from, to)))
+            .collect()
+    }
+
+    /// Number of slides `player` could make in the current position.
+    fn mobility(&self, player: Field) -> i32 {
+        let dims = Dimensions::default();
+        (0..CELLS)
+            .filter(|from| self.board[*from] == player)
+            .map(|from| dims.adjacent(from).iter().filter(|to| self.board[**to] == Field::Free).count() as i32)
+            .sum()
+    }
+}
+
+impl Variant for Morris {
+    fn name(&self) -> String {
+        "Three Men's Morris".to_string()
+    }
+
+    fn restart(&self) -> Self {
+        Morris::new()
+    }
+
+    fn to_move(&self) -> Field {
+        self.to_move
+    }
+
+    fn history(&self) -> &[usize] {
+        &self.history
+    }
+
+    fn legal_moves(&self) -> Vec<usize> {
+        if self.outcome().is_some() {
+            return vec![];
+        }
+        self.moves()
+    }
+
+    fn play(&mut self, mv: usize) -> Result<(), IllegalMove> {
+        if self.outcome().is_some() {
+            return Err(IllegalMove::GameOver);
+        }
+        if mv >= CELLS + CELLS * CELLS {
+            return Err(IllegalMove::OutOfBoard);
+        }
+        let (from, to) = move_cells(mv);
+        match from {
+            None if !self.placing() => return Err(IllegalMove::AllPlaced),
+            Some(_) if self.placing() => return Err(IllegalMove::StillPlacing),
+            Some(from) if self.board[from] != self.to_move => return Err(IllegalMove::NotYourMark),
+            _ => {}
+        }
+        if self.board[to] != Field::Free {
+            return Err(IllegalMove::Occupied(self.board[to]));
+        }
+        if let Some(from) = from {
+            if !Dimensions::default().adjacent(from).contains(&to) {
+                return Err(IllegalMove::NotAdjacent);
+            }
+            self.board[from] = Field::Free;
+        }
+        self.board[to] = self.to_move;
+        self.history.push(mv);
+        self.to_move = opponent(self.to_move);
+        self.positions.push(self.position_key());
+        Ok(())
+    }
+
+    fn undo(&mut self) -> Option<usize> {
+        let mv = self.history.pop()?;
+        self.positions.pop();
+        let (from, to) = move_cells(mv);
+        self.to_move = self.board[to];
+        if let Some(from) = from {
+            self.board[from] = self.to_move;
+        }
+        self.board[to] = Field::Free;
+        Some(mv)
+    }
+
+    fn outcome(&self) -> Option<Outcome> {
+        if let Some(line) = self.winning_line() {
+            return Some(Outcome::Won(self.board[line[0]]));
+        }
+        if self.moves().is_empty() {
+            return Some(Outcome::Won(opponent(self.to_move)));
+        }
+        let key = self.positions.last()?;
+        if self.positions.iter().filter(|position| *position == key).count() >= REPETITIONS {
+            return Some(Outcome::Draw);
+        }
+        None
+    }
+
+    fn move_name(&self, mv: usize) -> String {
+        let dims = Dimensions::default();
+        match move_cells(mv) {
+            (None, to) => dims.cell_name(to),
+            (Some(from), to) => format!("{}-{}", dims.cell_name(from), dims.cell_name(to)),
+        }
+    }
+
+    fn evaluate(&self, player: Field) -> i32 {
+        let lines: i32 = Dimensions::default()
+            .lines()
+            .iter()
+            .map(|line| {
+                let own = line.iter().filter(|cell| self.board[**cell] == player).count();
+                let theirs = line.iter().filter(|cell| self.board[**cell] == opponent(player)).count();
+                match (own, theirs) {
+                    (own, 0) => LINE_VALUES[own.min(MARKS - 1)],
+                    (0, theirs) => -LINE_VALUES[theirs.min(MARKS - 1)],
+                    _ => 0,
+                }
+            })
+            .sum();
+        let mobility = MOBILITY_VALUE * (self.mobility(player) - self.mobility(opponent(player)));
+        (lines + mobility).clamp(-WIN / 2, WIN / 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn game_after(moves: &[usize]) -> Morris {
+        let mut game = Morris::new();
+        for mv in moves {
+            game.play(*mv).unwrap();
+        }
+        game
+    }
+    #[test]
+    fn test_slides_to_adjacent_cells() {
+        assert_eq!(Err(IllegalMove::StillPlacing), game_after(&[0, 1]).play(slide(0, 4)));
+        let mut game = game_after(&[0, 1, 5, 3, 7, 8]);
+        assert!(!game.placing());
+        assert_eq!(Err(IllegalMove::AllPlaced), game.play(2));
+        assert_eq!(Err(IllegalMove::NotYourMark), game.play(slide(1, 2)));
+        assert_eq!(Err(IllegalMove::NotAdjacent), game.play(slide(0, 2)));
+        assert_eq!(Err(IllegalMove::Occupied(Field::Circle)), game.play(slide(0, 1)));
+        assert_eq!(vec![slide(0, 4), slide(5, 2), slide(5, 4), slide(7, 4), slide(7, 6)], game.legal_moves());
+        game.play(slide(0, 4)).unwrap();
+        assert_eq!(Field::Free, game.board()[0]);
+        assert_eq!(Some(slide(0, 4)), game.undo());
+        assert_eq!(Field::Cross, game.board()[0]);
+        assert_eq!("a1-b2", game.move_name(slide(0, 4)));
+    }
+    #[test]
+    fn test_draw_by_repetition() {
+        let mut game = game_after(&[0, 1, 5, 3, 7, 8]);
+        for _ in 0..2 {
+            assert_eq!(None, game.outcome());
+            for mv in [slide(7, 6), slide(1, 2), slide(6, 7), slide(2, 1)] {
+                game.play(mv).unwrap();
+            }
+        }
+        assert_eq!(Some(Outcome::Draw), game.outcome());
+    }
+    #[test]
+    fn test_ai_completes_diagonal() {
+        // the cross on b1 slides along the diagonal of a3 and c1 to the center
+        let game = game_after(&[1, 3, 2, 7, 6, 8]);
+        assert_eq!(Some(slide(1, 4)), game.ai_move(2, Duration::from_millis(200)));
+    }
+}
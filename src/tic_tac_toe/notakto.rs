@@ -0,0 +1,339 @@
+extern crate rand;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::time::Duration;
+use super::{Dimensions, Field};
+use super::check_functions::lines_3x3;
+use super::session::{IllegalMove, Outcome};
+use super::solver::opponent;
+use super::symmetry::Symmetry;
+use super::variant::{random_move_chance, Variant, WIN};
+
+/// Number of cells of a board.
+pub const CELLS: usize = 9;
+
+/// Canonical forms of the boards without a line, grouped by their value in the misère
+/// quotient. Bit `i` of a form is set if cell `i` holds a mark; the canonical form is the
+/// smallest of the eight symmetric boards.
+const BOARD_VALUES: [(Quotient, &[u16]); 8] = [
+    (Quotient::new(0, 0, 0, 0), &[0x001, 0x002, 0x062]),
+    (Quotient::new(0, 0, 1, 0), &[0x000]),
+    (Quotient::new(0, 0, 0, 1), &[0x003]),
+    (Quotient::new(0, 1, 0, 0), &[0x005, 0x00b, 0x00c, 0x011, 0x012, 0x01d, 0x01e, 0x02a, 0x02d, 0x063, 0x065, 0x06e, 0x071, 0x072, 0x0ab]),
+    (Quotient::new(1, 0, 0, 0), &[0x00a, 0x00d, 0x015, 0x01b, 0x01c, 0x028, 0x02b, 0x044, 0x061, 0x066, 0x06c, 0x073, 0x0aa, 0x0ad, 0x0e5, 0x0ee, 0x145]),
+    (Quotient::new(1, 0, 0, 1), &[0x00e, 0x029, 0x046]),
+    (Quotient::new(0, 0, 2, 0), &[0x010]),
+    (Quotient::new(1, 1, 0, 0), &[0x013, 0x01a, 0x045, 0x04e, 0x06a]),
+];
+
+/// Element of the misère quotient of Notakto, the monoid
+/// `<a, b, c, d | a² = 1, b³ = b, b²c = c, c³ = ac², b²d = d, cd = ad, d² = c²>`
+/// with 18 elements, written as `a^a b^b c^c d^d` in normal form.
+///
+/// The value of a position with several boards is the product of the values of its boards,
+/// and the player to move loses with perfect play exactly if it is one of `a`, `b²`, `bc`
+/// and `c²`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Quotient {
+    a: u8,
+    b: u8,
+    c: u8,
+    d: u8,
+}
+
+impl Quotient {
+    /// The value of a dead board, which changes nothing.
+    pub const ONE: Quotient = Quotient::new(0, 0, 0, 0);
+
+    const fn new(a: u8, b: u8, c: u8, d: u8) -> Quotient {
+        Quotient { a, b, c, d }
+    }
+
+    /// Return the element in normal form by applying the relations until none fits.
+    fn normalized(self) -> Quotient {
+        let Quotient { mut a, mut b, mut c, mut d } = self;
+        loop {
+            if d >= 2 {
+                d -= 2;
+                c += 2;
+            }
+            else if (c > 0 && d > 0) || c >= 3 {
+                c -= 1;
+                a += 1;
+            }
+            else if b >= 3 || (b == 2 && c + d > 0) {
+                b -= 2;
+            }
+            else {
+                break;
+            }
+        }
+        Quotient::new(a % 2, b, c, d)
+    }
+
+    #[must_use]
+    pub fn times(self, other: Quotient) -> Quotient {
+        Quotient::new(self.a + other.a, self.b + other.b, self.c + other.c, self.d + other.d).normalized()
+    }
+
+    /// Whether the player to move loses a position of this value with perfect play.
+    #[must_use]
+    pub fn is_lost(self) -> bool {
+        [Quotient::new(1, 0, 0, 0), Quotient::new(0, 2, 0, 0), Quotient::new(0, 1, 1, 0), Quotient::new(0, 0, 2, 0)].contains(&self)
+    }
+}
+
+/// Return the value of a board from the set cells of `mask`.
+fn board_value(mask: u16) -> Quotient {
+    if lines_3x3().iter().any(|line| line.iter().all(|cell| mask & 1 << cell != 0)) {
+        return Quotient::ONE;
+    }
+    let canonical = Symmetry::ALL
+        .iter()
+        .map(|symmetry| (0..CELLS).filter(|cell| mask & 1 << cell != 0).fold(0, |form, cell| form | 1 << symmetry.apply_index(cell)))
+        .min()
+        .unwrap_or(mask);
+    BOARD_VALUES
+        .iter()
+        .find(|(_, forms)| forms.contains(&canonical))
+        .map(|(value, _)| *value)
+        .expect("every board without a line has a value")
+}
+
+/// Notakto: both players place crosses on several 3x3 boards. A board with a line of three
+/// is dead and no longer played on, and whoever kills the last board loses.
+///
+/// Move `m` takes cell `m % CELLS` of board `m / CELLS`.
+#[derive(Clone, Debug)]
+pub struct Notakto {
+    boards: Vec<Vec<Field>>,
+    to_move: Field,
+    history: Vec<usize>,
+}
+
+impl Notakto {
+    /// Start a game on the given number of boards.
+    #[must_use]
+    pub fn new(boards: usize) -> Notakto {
+        Notakto { boards: vec![vec![Field::Free; CELLS]; boards.max(1)], to_move: Field::Cross, history: vec![] }
+    }
+
+    #[must_use]
+    pub fn board_count(&self) -> usize {
+        self.boards.len()
+    }
+
+    #[must_use]
+    pub fn board(&self, board: usize) -> &[Field] {
+        &self.boards[board]
+    }
+
+    /// Return the line that killed a board, if there is one.
+    #[must_use]
+    pub fn dead_line(&self, board: usize) -> Option<Vec<usize>> {
+        let cells = &self.boards[board];
+        lines_3x3().iter().find(|line| line.iter().all(|cell| cells[*cell] != Field::Free)).cloned()
+    }
+
+    #[must_use]
+    pub fn is_dead(&self, board: usize) -> bool {
+        self.dead_line(board).is_some()
+    }
+
+    fn mask(&self, board: usize) -> u16 {
+        (0..CELLS).filter(|cell| self.boards[board][*cell] != Field::Free).fold(0, |mask, cell| mask | 1 << cell)
+    }
+
+    /// Return the value of the position in the misère quotient.
+    #[must_use]
+    pub fn value(&self) -> Quotient {
+        (0..self.boards.len()).fold(Quotient::ONE, |value, board| value.times(board_value(self.mask(board))))
+    }
+
+    /// Return the value of the position after a move without making it.
+    fn value_after(&self, mv: usize) -> Quotient {
+        let (board, cell) = (mv / CELLS, mv % CELLS);
+        (0..self.boards.len()).fold(Quotient::ONE, |value, index| {
+            let mask = if index == board { self.mask(index) | 1 << cell } else { self.mask(index) };
+            value.times(board_value(mask))
+        })
+    }
+
+    /// Return whether a move kills the last living board.
+    fn loses_at_once(&self, mv: usize) -> bool {
+        let mut game = self.clone();
+        game.boards[mv / CELLS][mv % CELLS] = Field::Cross;
+        game.all_dead()
+    }
+
+    fn all_dead(&self) -> bool {
+        (0..self.boards.len()).all(|board| self.is_dead(board))
+    }
+}
+
+impl Variant for Notakto {
+    fn name(&self) -> String {
+        match self.boards.len() {
+            1 => "Notakto".to_string(),
+            boards => format!("Notakto on {boards} boards"),
+        }
+    }
+
+    fn restart(&self) -> Self {
+        Notakto::new(self.boards.len())
+    }
+
+    fn to_move(&self) -> Field {
+        self.to_move
+    }
+
+    /// Both players place crosses, so they are told apart by their turn.
+    fn player_name(&self, player: Field) -> String {
+        match player {
+            Field::Cross => "Player 1".to_string(),
+            _ => "Player 2".to_string(),
+        }
+    }
+
+    fn history(&self) -> &[usize] {
+        &self.history
+    }
+
+    fn legal_moves(&self) -> Vec<usize> {
+        (0..self.boards.len())
+            .filter(|board| !self.is_dead(*board))
+            .flat_map(|board| (0..CELLS).filter(move |cell| self.boards[board][*cell] == Field::Free).map(move |cell| board * CELLS + cell))
+            .collect()
+    }
+
+    fn play(&mut self, mv: usize) -> Result<(), IllegalMove> {
+        if self.all_dead() {
+            return Err(IllegalMove::GameOver);
+        }
+        if mv >= self.boards.len() * CELLS {
+            return Err(IllegalMove::OutOfBoard);
+        }
+        let (board, cell) = (mv / CELLS, mv % CELLS);
+        if self.is_dead(board) {
+            return Err(IllegalMove::DeadBoard);
+        }
+        if self.boards[board][cell] != Field::Free {
+            return Err(IllegalMove::Occupied(self.boards[board][cell]));
+        }
+        self.boards[board][cell] = Field::Cross;
+        self.history.push(mv);
+        self.to_move = opponent(self.to_move);
+        Ok(())
+    }
+
+    fn undo(&mut self) -> Option<usize> {
+        let mv = self.history.pop()?;
+        self.boards[mv / CELLS][mv % CELLS] = Field::Free;
+        self.to_move = opponent(self.to_move);
+        Some(mv)
+    }
+
+    /// The player who killed the last board loses, so the one to move wins.
+    fn outcome(&self) -> Option<Outcome> {
+        self.all_dead().then_some(Outcome::Won(self.to_move))
+    }
+
+    fn move_name(&self, mv: usize) -> String {
+        let cell = Dimensions::default().cell_name(mv % CELLS);
+        match self.boards.len() {
+            1 => cell,
+            _ => format!("{}:{cell}", mv / CELLS + 1),
+        }
+    }
+
+    /// The quotient tells the result of every position with perfect play.
+    fn evaluate(&self, player: Field) -> i32 {
+        let value = if self.value().is_lost() { -WIN / 2 } else { WIN / 2 };
+        if player == self.to_move { value } else { -value }
+    }
+
+    /// The moves into a lost position for the opponent, or all moves if there are none.
+    fn candidate_moves(&self) -> Vec<usize> {
+        let moves = self.legal_moves();
+        let winning: Vec<usize> = moves.iter().copied().filter(|mv| self.value_after(*mv).is_lost()).collect();
+        if winning.is_empty() { moves } else { winning }
+    }
+
+    /// Play perfectly by the misère quotient. In a lost position, a move that keeps a board
+    /// alive is chosen at random, which gives the opponent the most chances to go wrong.
+    fn ai_move(&self, difficulty: i32, _think_time: Duration) -> Option<usize> {
+        let moves = self.legal_moves();
+        let mut rng = rand::thread_rng();
+        if rng.gen_bool(random_move_chance(difficulty)) {
+            return moves.choose(&mut rng).copied();
+        }
+        let winning: Vec<usize> = moves.iter().copied().filter(|mv| self.value_after(*mv).is_lost()).collect();
+        if let Some(mv) = winning.choose(&mut rng) {
+            return Some(*mv);
+        }
+        let surviving: Vec<usize> = moves.iter().copied().filter(|mv| !self.loses_at_once(*mv)).collect();
+        surviving.choose(&mut rng).or_else(|| moves.choose(&mut rng)).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Return whether the player to move wins, by searching the whole game tree.
+    fn wins(game: &mut Notakto) -> bool {
+        if game.outcome().is_some() {
+            return true;
+        }
+        game.legal_moves().into_iter().any(|mv| {
+            game.play(mv).unwrap();
+            let lost = !wins(game);
+            game.undo();
+            lost
+        })
+    }
+
+    #[test]
+    fn test_quotient_matches_search() {
+        // a single empty board is won by the center, two of them are lost
+        assert!(!Notakto::new(1).value().is_lost());
+        assert_eq!(Quotient::new(0, 0, 2, 0), Notakto::new(2).value());
+        assert!(Notakto::new(2).value().is_lost());
+        // positions small enough to search them completely
+        let mut game = Notakto::new(2);
+        for mv in [4, 9, 0, 17, 2, 13] {
+            game.play(mv).unwrap();
+            assert_eq!(!game.value().is_lost(), wins(&mut game.clone()));
+        }
+    }
+    #[test]
+    fn test_killing_last_board_loses() {
+        let mut game = Notakto::new(2);
+        for mv in [0, 1, 2] {
+            game.play(mv).unwrap();
+        }
+        assert!(game.is_dead(0));
+        assert_eq!(Err(IllegalMove::DeadBoard), game.play(3));
+        assert_eq!(None, game.outcome());
+        for mv in [9, 10, 11] {
+            game.play(mv).unwrap();
+        }
+        // the second player killed the last board
+        assert_eq!(Some(Outcome::Won(Field::Cross)), game.outcome());
+        assert_eq!("2:c1", game.move_name(11));
+    }
+    #[test]
+    fn test_ai_plays_perfectly() {
+        let mut game = Notakto::new(3);
+        while game.outcome().is_none() {
+            let lost = game.value().is_lost();
+            let mv = game.ai_move(2, Duration::from_millis(10)).unwrap();
+            game.play(mv).unwrap();
+            if !lost {
+                assert!(game.value().is_lost());
+            }
+        }
+        // the first player wins on three boards
+        assert_eq!(Some(Outcome::Won(Field::Cross)), game.outcome());
+    }
+}
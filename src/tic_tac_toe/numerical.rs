@@ -0,0 +1,347 @@
+extern crate rand;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use super::{Dimensions, Field, Number};
+use super::check_functions::{cats_game, line_with_sum, lines_3x3};
+use super::session::{IllegalMove, Outcome};
+use super::solver::opponent;
+use super::symmetry::Symmetry;
+use super::variant::{random_move_chance, Variant, WIN};
+
+/// Number of cells of the board.
+pub const CELLS: usize = 9;
+/// Sum of the numbers in a winning line.
+pub const TOTAL: u8 = 15;
+
+/// Return the move placing `number` on `cell`.
+#[must_use]
+pub fn number_move(cell: usize, number: u8) -> usize {
+    usize::from(number - 1) * CELLS + cell
+}
+
+/// Return the cell and the number of a move.
+#[must_use]
+pub fn move_number(mv: usize) -> (usize, u8) {
+    (mv % CELLS, (mv / CELLS) as u8 + 1)
+}
+
+/// Return the numbers of `player`: the odd ones for the first player, the even ones for the
+/// second.
+#[must_use]
+pub fn numbers_of(player: Field) -> Vec<u8> {
+    let first = if player == Field::Cross { 1 } else { 2 };
+    (first..=9).step_by(2).collect()
+}
+
+/// Return the player who places `number`, the counterpart of `numbers_of`.
+#[must_use]
+pub fn owner_of(number: u8) -> Field {
+    if number % 2 == 1 { Field::Cross } else { Field::Circle }
+}
+
+/// Numerical tic-tac-toe on the 3x3 board: the first player places the odd numbers from 1 to
+/// 9, the second one the even numbers, every number once, and whoever completes a line that
+/// adds up to 15 wins.
+///
+/// Move `m` places the number `m / CELLS + 1` on cell `m % CELLS`, see `number_move`.
+#[derive(Clone, Debug)]
+pub struct Numerical {
+    board: Vec<Number>,
+    to_move: Field,
+    history: Vec<usize>,
+}
+
+impl Default for Numerical {
+    fn default() -> Self {
+        Numerical::new()
+    }
+}
+
+impl Numerical {
+    #[must_use]
+    pub fn new() -> Numerical {
+        Numerical { board: vec![None; CELLS], to_move: Field::Cross, history: vec![] }
+    }
+
+    #[must_use]
+    pub fn board(&self) -> &[Number] {
+        &self.board
+    }
+
+    /// Return the numbers the player to move has not placed yet.
+    #[must_use]
+    pub fn available_numbers(&self) -> Vec<u8> {
+        numbers_of(self.to_move).into_iter().filter(|number| !self.board.contains(&Some(*number))).collect()
+    }
+
+    /// Return the cells of the line that won the game, if there is one.
+    #[must_use]
+    pub fn winning_line(&self) -> Option<Vec<usize>> {
+        line_with_sum(&self.board, &Dimensions::default(), TOTAL)
+    }
+
+    /// Whether a line adds up to 15, which is checked faster than finding the line.
+    fn has_winning_line(&self) -> bool {
+        lines_3x3().iter().any(|line| {
+            let numbers: Option<Vec<u8>> = line.iter().map(|cell| self.board[*cell]).collect();
+            numbers.is_some_and(|numbers| numbers.iter().sum::<u8>() == TOTAL)
+        })
+    }
+
+    /// Return the moves with one of `numbers` that complete a line adding up to 15.
+    fn completing_moves_with(&self, numbers: &[u8]) -> Vec<usize> {
+        lines_3x3()
+            .iter()
+            .filter_map(|line| {
+                let free: Vec<usize> = line.iter().copied().filter(|cell| self.board[*cell].is_none()).collect();
+                let sum: u8 = line.iter().filter_map(|cell| self.board[*cell]).sum();
+                match free.as_slice() {
+                    &[cell] if sum < TOTAL && numbers.contains(&(TOTAL - sum)) => Some(number_move(cell, TOTAL - sum)),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Return the moves that complete a line adding up to 15.
+    fn completing_moves(&self) -> Vec<usize> {
+        self.completing_moves_with(&self.available_numbers())
+    }
+
+    /// Return the cells where the opponent could complete a line with their next move.
+    fn threatened_cells(&self) -> Vec<usize> {
+        let numbers: Vec<u8> = numbers_of(opponent(self.to_move)).into_iter().filter(|number| !self.board.contains(&Some(*number))).collect();
+        let mut cells: Vec<usize> = self.completing_moves_with(&numbers).into_iter().map(|mv| move_number(mv).0).collect();
+        cells.sort_unstable();
+        cells.dedup();
+        cells
+    }
+}
+
+impl Variant for Numerical {
+    fn name(&self) -> String {
+        "Numerical tic-tac-toe".to_string()
+    }
+
+    fn restart(&self) -> Self {
+        Numerical::new()
+    }
+
+    fn to_move(&self) -> Field {
+        self.to_move
+    }
+
+    fn player_name(&self, player: Field) -> String {
+        match player {
+            Field::Cross => "Odd".to_string(),
+            _ => "Even".to_string(),
+        }
+    }
+
+    fn history(&self) -> &[usize] {
+        &self.history
+    }
+
+    fn legal_moves(&self) -> Vec<usize> {
+        if self.outcome().is_some() {
+            return vec![];
+        }
+        let free: Vec<usize> = (0..CELLS).filter(|cell| self.board[*cell].is_none()).collect();
+        self.available_numbers().iter().flat_map(|number| free.iter().map(|cell| number_move(*cell, *number))).collect()
+    }
+
+    fn play(&mut self, mv: usize) -> Result<(), IllegalMove> {
+        if self.outcome().is_some() {
+            return Err(IllegalMove::GameOver);
+        }
+        if mv >= CELLS * CELLS {
+            return Err(IllegalMove::OutOfBoard);
+        }
+        let (cell, number) = move_number(mv);
+        if let Some(placed) = self.board[cell] {
+            return Err(IllegalMove::Occupied(owner_of(placed)));
+        }
+        if !self.available_numbers().contains(&number) {
+            return Err(IllegalMove::NumberUnavailable);
+        }
+        self.board[cell] = Some(number);
+        self.history.push(mv);
+        self.to_move = opponent(self.to_move);
+        Ok(())
+    }
+
+    fn undo(&mut self) -> Option<usize> {
+        let mv = self.history.pop()?;
+        self.board[move_number(mv).0] = None;
+        self.to_move = opponent(self.to_move);
+        Some(mv)
+    }
+
+    /// The line belongs to whoever completed it, whoever placed its other numbers.
+    fn outcome(&self) -> Option<Outcome> {
+        if self.has_winning_line() {
+            Some(Outcome::Won(opponent(self.to_move)))
+        }
+        else if cats_game(&self.board) {
+            Some(Outcome::Draw)
+        }
+        else {
+            None
+        }
+    }
+
+    fn move_name(&self, mv: usize) -> String {
+        let (cell, number) = move_number(mv);
+        format!("{number} {}", Dimensions::default().cell_name(cell))
+    }
+
+    fn evaluate(&self, player: Field) -> i32 {
+        let value = if self.completing_moves().is_empty() { 0 } else { WIN / 2 };
+        if player == self.to_move { value } else { -value }
+    }
+
+    /// Play the best move the solver finds. The moves are shuffled, so the AI does not play the
+    /// same game every time.
+    fn ai_move(&self, difficulty: i32, _think_time: Duration) -> Option<usize> {
+        let mut moves = self.legal_moves();
+        let mut rng = rand::thread_rng();
+        if rng.gen_bool(random_move_chance(difficulty)) {
+            return moves.choose(&mut rng).copied();
+        }
+        moves.shuffle(&mut rng);
+        shared_solver().lock().ok()?.best_move(self, &moves)
+    }
+}
+
+/// Return the solver shared by all games, which keeps the positions it solved.
+fn shared_solver() -> &'static Mutex<NumericalSolver> {
+    static SOLVER: OnceLock<Mutex<NumericalSolver>> = OnceLock::new();
+    SOLVER.get_or_init(|| Mutex::new(NumericalSolver::new()))
+}
+
+/// Perfect-play solver for Numerical tic-tac-toe.
+///
+/// Values are given from the point of view of the player to move: 1 is a win, -1 a loss and
+/// 0 a draw. Unlike `Solver`, wins are not told apart by their length, which lets the search
+/// stop at the first winning move. Positions are cached by their canonical form; whose turn
+/// it is follows from the number of placed numbers.
+#[derive(Default)]
+pub struct NumericalSolver {
+    table: HashMap<u32, i32>,
+}
+
+impl NumericalSolver {
+    #[must_use]
+    pub fn new() -> NumericalSolver {
+        NumericalSolver::default()
+    }
+
+    /// Return the game theoretic value of the position for the player to move.
+    pub fn evaluate(&mut self, game: &Numerical) -> i32 {
+        self.negamax(&mut game.clone())
+    }
+
+    /// Return the best of `moves` for the player to move, trying them in the given order and
+    /// taking the first winning one.
+    pub fn best_move(&mut self, game: &Numerical, moves: &[usize]) -> Option<usize> {
+        let mut game = game.clone();
+        let mut best: Option<(usize, i32)> = None;
+        for mv in moves {
+            if game.play(*mv).is_err() {
+                continue;
+            }
+            let value = -self.negamax(&mut game);
+            game.undo();
+            if best.is_none_or(|(_, best_value)| value > best_value) {
+                best = Some((*mv, value));
+            }
+            if value == 1 {
+                break;
+            }
+        }
+        best.map(|(mv, _)| mv)
+    }
+
+    /// Encode the board in base 10, with 0 for a free cell, and return the smallest encoding
+    /// of the equivalent boards.
+    fn key(board: &[Number]) -> u32 {
+        Symmetry::ALL
+            .iter()
+            .map(|symmetry| (0..CELLS).rev().fold(0, |key, cell| key * 10 + u32::from(board[symmetry.original_index(cell)].unwrap_or(0))))
+            .min()
+            .unwrap_or(0)
+    }
+
+    fn negamax(&mut self, game: &mut Numerical) -> i32 {
+        let key = NumericalSolver::key(&game.board);
+        if let Some(value) = self.table.get(&key) {
+            return *value;
+        }
+        let value = match game.outcome() {
+            Some(Outcome::Won(_)) => -1,
+            Some(Outcome::Draw) => 0,
+            None if !game.completing_moves().is_empty() => 1,
+            None => match game.threatened_cells().as_slice() {
+                // the opponent completes one of the lines after any move
+                [_, _, ..] => -1,
+                threats => {
+                    let mut best = -1;
+                    for mv in game.legal_moves() {
+                        // a move beside the threatened cell loses at once
+                        if threats.first().is_some_and(|cell| *cell != move_number(mv).0) {
+                            continue;
+                        }
+                        game.play(mv).expect("legal moves can be played");
+                        best = best.max(-self.negamax(game));
+                        game.undo();
+                        if best == 1 {
+                            break;
+                        }
+                    }
+                    best
+                }
+            },
+        };
+        self.table.insert(key, value);
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_adding_up_to_fifteen_wins() {
+        let mut game = Numerical::new();
+        for mv in [number_move(4, 5), number_move(0, 2), number_move(8, 1), number_move(2, 4)] {
+            game.play(mv).unwrap();
+        }
+        assert_eq!(Err(IllegalMove::NumberUnavailable), game.play(number_move(3, 5)));
+        assert_eq!(Err(IllegalMove::NumberUnavailable), game.play(number_move(3, 6)));
+        assert_eq!(Err(IllegalMove::Occupied(Field::Cross)), game.play(number_move(4, 7)));
+        assert_eq!(Err(IllegalMove::Occupied(Field::Circle)), game.play(number_move(0, 7)));
+        // the full diagonal adds up to 8, 2 and 4 in the bottom row need a 9
+        assert_eq!(None, game.outcome());
+        assert_eq!(vec![number_move(1, 9)], game.completing_moves());
+        game.play(number_move(1, 9)).unwrap();
+        assert_eq!(Some(Outcome::Won(Field::Cross)), game.outcome());
+        assert_eq!(Some(vec![0, 1, 2]), game.winning_line());
+        assert_eq!("9 b1", game.move_name(number_move(1, 9)));
+    }
+    #[test]
+    fn test_first_player_wins() {
+        let mut solver = NumericalSolver::new();
+        assert!(solver.evaluate(&Numerical::new()) > 0);
+        // the hard AI keeps the win against any answers
+        let mut game = Numerical::new();
+        while game.outcome().is_none() {
+            let mv = if game.to_move() == Field::Cross { game.ai_move(2, Duration::from_millis(10)).unwrap() } else { game.legal_moves()[0] };
+            game.play(mv).unwrap();
+        }
+        assert_eq!(Some(Outcome::Won(Field::Cross)), game.outcome());
+    }
+}
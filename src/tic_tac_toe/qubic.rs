@@ -0,0 +1,314 @@
+use std::sync::OnceLock;
+use super::{Dimensions, Field};
+use super::session::{IllegalMove, Outcome};
+use super::solver::opponent;
+use super::variant::{Variant, WIN};
+
+/// Number of cells along each edge of the cube.
+pub const SIZE: usize = 4;
+/// Number of cells of a layer.
+pub const LAYER: usize = SIZE * SIZE;
+/// Number of cells of the cube.
+pub const CELLS: usize = LAYER * SIZE;
+
+/// Value of the lines for the search, by the number of marks of a single player in them.
+const LINE_VALUES: [i32; SIZE] = [0, 1, 8, 64];
+
+/// A 4x4x4 board made of four layers of 4x4 cells.
+///
+/// Cell `i` is in layer `i / 16`, row `i % 16 / 4` and column `i % 4`; within a layer cells
+/// are numbered like the cells of a 4x4 board.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cube {
+    cells: Vec<Field>,
+}
+
+impl Default for Cube {
+    fn default() -> Self {
+        Cube::new()
+    }
+}
+
+impl Cube {
+    #[must_use]
+    pub fn new() -> Cube {
+        Cube { cells: vec![Field::Free; CELLS] }
+    }
+
+    /// Return the cell at the given coordinates.
+    #[must_use]
+    pub fn index(layer: usize, row: usize, column: usize) -> usize {
+        layer * LAYER + row * SIZE + column
+    }
+
+    /// Return the layer, row and column of a cell.
+    #[must_use]
+    pub fn coordinates(cell: usize) -> (usize, usize, usize) {
+        (cell / LAYER, cell % LAYER / SIZE, cell % SIZE)
+    }
+
+    #[must_use]
+    pub fn cells(&self) -> &[Field] {
+        &self.cells
+    }
+
+    /// Return the cells of a layer, numbered like a 4x4 board.
+    #[must_use]
+    pub fn layer(&self, layer: usize) -> &[Field] {
+        &self.cells[layer * LAYER..(layer + 1) * LAYER]
+    }
+
+    /// Return all 76 lines: 48 rows and columns, 16 vertical lines through the layers, 8
+    /// diagonals within the layers, 16 diagonals through the layers and 4 space diagonals.
+    #[must_use]
+    pub fn lines() -> &'static [[usize; SIZE]] {
+        static LINES: OnceLock<Vec<[usize; SIZE]>> = OnceLock::new();
+        LINES.get_or_init(|| {
+            let mut lines = vec![];
+            let size = SIZE as isize;
+            for dl in -1..=1 {
+                for dr in -1..=1 {
+                    for dc in -1..=1 {
+                        // every line is found from both ends, only keep one direction
+                        if (dl, dr, dc) <= (0, 0, 0) {
+                            continue;
+                        }
+                        for start in 0..CELLS {
+                            let (layer, row, column) = Cube::coordinates(start);
+                            let cell_at = |step: isize| {
+                                let (l, r, c) = (layer as isize + dl * step, row as isize + dr * step, column as isize + dc * step);
+                                let inside = (0..size).contains(&l) && (0..size).contains(&r) && (0..size).contains(&c);
+                                inside.then(|| Cube::index(l as usize, r as usize, c as usize))
+                            };
+                            // lines run from edge to edge
+                            if cell_at(-1).is_some() {
+                                continue;
+                            }
+                            let cells: Option<Vec<usize>> = (0..size).map(cell_at).collect();
+                            if let Some(cells) = cells {
+                                lines.push([cells[0], cells[1], cells[2], cells[3]]);
+                            }
+                        }
+                    }
+                }
+            }
+            lines
+        })
+    }
+
+    /// Return the indices in `lines()` of the lines through every cell.
+    fn cell_lines() -> &'static [Vec<usize>] {
+        static CELL_LINES: OnceLock<Vec<Vec<usize>>> = OnceLock::new();
+        CELL_LINES.get_or_init(|| {
+            (0..CELLS)
+                .map(|cell| (0..Cube::lines().len()).filter(|line| Cube::lines()[*line].contains(&cell)).collect())
+                .collect()
+        })
+    }
+
+    /// Return the number of marks of `player` and of the opponent in a line.
+    fn count(&self, line: &[usize; SIZE], player: Field) -> (usize, usize) {
+        let own = line.iter().filter(|cell| self.cells[**cell] == player).count();
+        let theirs = line.iter().filter(|cell| self.cells[**cell] == opponent(player)).count();
+        (own, theirs)
+    }
+}
+
+/// Qubic: four in a row on a 4x4x4 cube.
+///
+/// Move `m` takes cell `m` of the cube.
+#[derive(Clone, Debug)]
+pub struct Qubic {
+    cube: Cube,
+    to_move: Field,
+    history: Vec<usize>,
+    /// Index in `Cube::lines()` of the line that won the game.
+    won_line: Option<usize>,
+}
+
+impl Default for Qubic {
+    fn default() -> Self {
+        Qubic::new()
+    }
+}
+
+impl Qubic {
+    #[must_use]
+    pub fn new() -> Qubic {
+        Qubic { cube: Cube::new(), to_move: Field::Cross, history: vec![], won_line: None }
+    }
+
+    #[must_use]
+    pub fn cube(&self) -> &Cube {
+        &self.cube
+    }
+
+    /// Return the cells of the line that won the game, if there is one.
+    #[must_use]
+    pub fn winning_line(&self) -> Option<[usize; SIZE]> {
+        self.won_line.map(|line| Cube::lines()[line])
+    }
+
+    /// Return the free cells that complete a line of `player`.
+    fn completing_cells(&self, player: Field) -> Vec<usize> {
+        let mut cells: Vec<usize> = Cube::lines()
+            .iter()
+            .filter(|line| self.cube.count(line, player) == (SIZE - 1, 0))
+            .filter_map(|line| line.iter().copied().find(|cell| self.cube.cells[*cell] == Field::Free))
+            .collect();
+        cells.sort_unstable();
+        cells.dedup();
+        cells
+    }
+
+    /// Value of a free cell for move ordering: cells on many open lines with marks of a
+    /// single player come first.
+    fn cell_value(&self, cell: usize) -> i32 {
+        let player = self.to_move;
+        Cube::cell_lines()[cell]
+            .iter()
+            .map(|line| match self.cube.count(&Cube::lines()[*line], player) {
+                (own, 0) => LINE_VALUES[own] + 1,
+                (0, theirs) => LINE_VALUES[theirs] + 1,
+                _ => 0,
+            })
+            .sum()
+    }
+}
+
+impl Variant for Qubic {
+    fn name(&self) -> String {
+        "Qubic (4x4x4)".to_string()
+    }
+
+    fn restart(&self) -> Self {
+        Qubic::new()
+    }
+
+    fn to_move(&self) -> Field {
+        self.to_move
+    }
+
+    fn history(&self) -> &[usize] {
+        &self.history
+    }
+
+    fn legal_moves(&self) -> Vec<usize> {
+        if self.outcome().is_some() {
+            return vec![];
+        }
+        (0..CELLS).filter(|cell| self.cube.cells[*cell] == Field::Free).collect()
+    }
+
+    fn play(&mut self, mv: usize) -> Result<(), IllegalMove> {
+        if self.outcome().is_some() {
+            return Err(IllegalMove::GameOver);
+        }
+        if mv >= CELLS {
+            return Err(IllegalMove::OutOfBoard);
+        }
+        if self.cube.cells[mv] != Field::Free {
+            return Err(IllegalMove::Occupied(self.cube.cells[mv]));
+        }
+        self.cube.cells[mv] = self.to_move;
+        self.history.push(mv);
+        // only lines through the new mark can have been completed
+        self.won_line = Cube::cell_lines()[mv].iter().copied().find(|line| self.cube.count(&Cube::lines()[*line], self.to_move).0 == SIZE);
+        self.to_move = opponent(self.to_move);
+        Ok(())
+    }
+
+    fn undo(&mut self) -> Option<usize> {
+        let mv = self.history.pop()?;
+        self.to_move = self.cube.cells[mv];
+        self.cube.cells[mv] = Field::Free;
+        self.won_line = None;
+        Some(mv)
+    }
+
+    fn outcome(&self) -> Option<Outcome> {
+        match self.won_line {
+            Some(line) => Some(Outcome::Won(self.cube.cells[Cube::lines()[line][0]])),
+            None if self.history.len() == CELLS => Some(Outcome::Draw),
+            None => None,
+        }
+    }
+
+    fn move_name(&self, mv: usize) -> String {
+        format!("{}:{}", mv / LAYER + 1, Dimensions::new(SIZE, SIZE, SIZE).cell_name(mv % LAYER))
+    }
+
+    fn evaluate(&self, player: Field) -> i32 {
+        let value: i32 = Cube::lines()
+            .iter()
+            .map(|line| match self.cube.count(line, player) {
+                (own, 0) => LINE_VALUES[own],
+                (0, theirs) => -LINE_VALUES[theirs],
+                _ => 0,
+            })
+            .sum();
+        value.clamp(-WIN / 2, WIN / 2)
+    }
+
+    /// Forced moves only: a line can be completed or a line of the opponent has to be
+    /// blocked. Otherwise all free cells, the most promising first.
+    fn candidate_moves(&self) -> Vec<usize> {
+        if self.outcome().is_some() {
+            return vec![];
+        }
+        let wins = self.completing_cells(self.to_move);
+        if !wins.is_empty() {
+            return wins;
+        }
+        let blocks = self.completing_cells(opponent(self.to_move));
+        if !blocks.is_empty() {
+            return blocks;
+        }
+        let mut moves = self.legal_moves();
+        moves.sort_by_cached_key(|cell| -self.cell_value(*cell));
+        moves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_lines() {
+        let lines = Cube::lines();
+        assert_eq!(76, lines.len());
+        // the corners and the inner cells are on seven lines, all others on four
+        assert_eq!(7, Cube::cell_lines()[0].len());
+        assert_eq!(7, Cube::cell_lines()[Cube::index(1, 1, 1)].len());
+        assert_eq!(4, Cube::cell_lines()[1].len());
+        assert!(lines.contains(&[0, 21, 42, 63]));
+        assert!(lines.contains(&[3, 22, 41, 60]));
+        assert!(lines.contains(&[12, 25, 38, 51]));
+    }
+    #[test]
+    fn test_space_diagonal_wins() {
+        let mut game = Qubic::new();
+        for mv in [0, 1, 21, 2, 42, 3] {
+            game.play(mv).unwrap();
+        }
+        assert_eq!(None, game.outcome());
+        game.play(63).unwrap();
+        assert_eq!(Some(Outcome::Won(Field::Cross)), game.outcome());
+        assert_eq!(Some([0, 21, 42, 63]), game.winning_line());
+        assert_eq!(Err(IllegalMove::GameOver), game.play(4));
+        assert_eq!(Some(63), game.undo());
+        assert_eq!(None, game.outcome());
+    }
+    #[test]
+    fn test_ai_blocks_line() {
+        let mut game = Qubic::new();
+        // circle has three marks in the vertical line through cell 5
+        for mv in [0, 5, 2, 21, 8, 37] {
+            game.play(mv).unwrap();
+        }
+        assert_eq!(vec![53], game.candidate_moves());
+        assert_eq!(Some(53), game.ai_move(2, Duration::from_millis(200)));
+    }
+}
@@ -0,0 +1,454 @@
+use std::fmt;
+use super::{Dimensions, Field, Rules};
+use super::check_functions::{cats_game, drop_cell, winner, winning_line};
+use super::solver::opponent;
+
+/// Largest number of rows or columns of a game read by `Game::from_text`, as columns are named
+/// by a single letter.
+pub const MAX_SIDE: usize = 26;
+
+/// Reason why a move was rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IllegalMove {
+    /// The cell is already taken by the given player.
+    Occupied(Field),
+    /// The cell is not on the board.
+    OutOfBoard,
+    /// The game is already over.
+    GameOver,
+    /// The move has to be made on another board.
+    WrongBoard,
+    /// With gravity, only the lowest free cell of a column can be taken.
+    Floating,
+    /// All marks are on the board, so a mark has to be moved instead of placed.
+    AllPlaced,
+    /// A mark can only be moved to a cell connected to its cell.
+    NotAdjacent,
+    /// Marks can only be moved once all of them are placed.
+    StillPlacing,
+    /// Only the marks of the player to move can be moved.
+    NotYourMark,
+    /// The board already has a line and is no longer played on.
+    DeadBoard,
+    /// The number belongs to the opponent or is already on the board.
+    NumberUnavailable,
+}
+
+impl fmt::Display for IllegalMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IllegalMove::Occupied(_) => write!(f, "This field is already taken"),
+            IllegalMove::OutOfBoard => write!(f, "This move is not possible"),
+            IllegalMove::GameOver => write!(f, "The game is over"),
+            IllegalMove::WrongBoard => write!(f, "You have to play on the highlighted board"),
+            IllegalMove::Floating => write!(f, "Marks fall to the lowest free cell of the column"),
+            IllegalMove::AllPlaced => write!(f, "All marks are placed, select one of yours to move it"),
+            IllegalMove::NotAdjacent => write!(f, "Marks can only move to a free cell connected to them"),
+            IllegalMove::StillPlacing => write!(f, "Place all your marks before you move one"),
+            IllegalMove::NotYourMark => write!(f, "You can only move your own marks"),
+            IllegalMove::DeadBoard => write!(f, "This board is dead, play on another one"),
+            IllegalMove::NumberUnavailable => write!(f, "This number is not available"),
+        }
+    }
+}
+
+/// Result of a finished game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Won(Field),
+    Draw,
+}
+
+/// Reason why a position that was set up freely cannot occur in a game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvalidPosition {
+    /// The board does not have one field per cell.
+    WrongSize,
+    /// Cross moves first, so there must be as many crosses as circles or one more.
+    MarkCount,
+    /// Both players have a complete line.
+    BothWon,
+    /// A mark was placed after the given player had already completed a line.
+    MoveAfterWin(Field),
+    /// With gravity, a mark is above a free cell.
+    Floating,
+}
+
+impl fmt::Display for InvalidPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidPosition::WrongSize => write!(f, "The board has the wrong number of cells"),
+            InvalidPosition::MarkCount => write!(f, "There must be as many crosses as circles or one cross more"),
+            InvalidPosition::BothWon => write!(f, "Both players have a complete line"),
+            InvalidPosition::MoveAfterWin(Field::Cross) => write!(f, "Circle moved after cross had already completed a line"),
+            InvalidPosition::MoveAfterWin(_) => write!(f, "Cross moved after circle had already completed a line"),
+            InvalidPosition::Floating => write!(f, "A mark floats above a free cell"),
+        }
+    }
+}
+
+/// Check that a position set up on the board can occur in a game and return the player to
+/// move.
+///
+/// # Errors
+///
+/// Returns why the position cannot be reached under `rules` when cross moves first and the
+/// game ends with the first complete line.
+pub fn validate_position(board: &[Field], dims: &Dimensions, rules: &Rules) -> Result<Field, InvalidPosition> {
+    if board.len() != dims.cells() {
+        return Err(InvalidPosition::WrongSize);
+    }
+    if rules.gravity && (dims.columns..board.len()).any(|cell| board[cell] != Field::Free && board[cell - dims.columns] == Field::Free) {
+        return Err(InvalidPosition::Floating);
+    }
+    let count = |player: Field| board.iter().filter(|cell| **cell == player).count();
+    let (crosses, circles) = (count(Field::Cross), count(Field::Circle));
+    if crosses != circles && crosses != circles + 1 {
+        return Err(InvalidPosition::MarkCount);
+    }
+    let has_line = |player: Field| dims.lines().iter().any(|line| line.iter().all(|cell| board[*cell] == player));
+    match (has_line(Field::Cross), has_line(Field::Circle)) {
+        (true, true) => Err(InvalidPosition::BothWon),
+        (true, false) if crosses == circles => Err(InvalidPosition::MoveAfterWin(Field::Cross)),
+        (false, true) if crosses != circles => Err(InvalidPosition::MoveAfterWin(Field::Circle)),
+        _ if crosses == circles => Ok(Field::Cross),
+        _ => Ok(Field::Circle),
+    }
+}
+
+/// State of a single game: the board, the player to move and the moves made so far.
+///
+/// Cross always makes the first move.
+#[derive(Clone, Debug)]
+pub struct Game {
+    dims: Dimensions,
+    rules: Rules,
+    board: Vec<Field>,
+    history: Vec<usize>,
+    to_move: Field,
+}
+
+impl Game {
+    #[must_use]
+    pub fn new(dims: Dimensions) -> Game {
+        Game::with_rules(dims, Rules::default())
+    }
+
+    #[must_use]
+    pub fn with_rules(dims: Dimensions, rules: Rules) -> Game {
+        Game {
+            dims,
+            rules,
+            board: vec![Field::Free; dims.cells()],
+            history: vec![],
+            to_move: Field::Cross,
+        }
+    }
+
+    #[must_use]
+    pub fn dims(&self) -> &Dimensions {
+        &self.dims
+    }
+
+    #[must_use]
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    #[must_use]
+    pub fn board(&self) -> &[Field] {
+        &self.board
+    }
+
+    /// Cells in the order they were taken.
+    #[must_use]
+    pub fn history(&self) -> &[usize] {
+        &self.history
+    }
+
+    #[must_use]
+    pub fn to_move(&self) -> Field {
+        self.to_move
+    }
+
+    /// Place the mark of the player to move in a cell.
+    ///
+    /// # Errors
+    ///
+    /// Returns why the move is not allowed if the cell is taken or not on the board, or if the
+    /// game is already over.
+    pub fn play(&mut self, index: usize) -> Result<(), IllegalMove> {
+        if self.outcome().is_some() {
+            return Err(IllegalMove::GameOver);
+        }
+        match self.board.get(index) {
+            None => Err(IllegalMove::OutOfBoard),
+            Some(Field::Free) if self.rules.gravity && drop_cell(&self.board, &self.dims, index % self.dims.columns) != Some(index) => Err(IllegalMove::Floating),
+            Some(Field::Free) => {
+                // the oldest mark vanishes before the line is checked, so it cannot be part of it
+                if let Some(oldest) = self.next_to_vanish(self.to_move) {
+                    self.board[oldest] = Field::Free;
+                }
+                self.board[index] = self.to_move;
+                self.history.push(index);
+                self.to_move = opponent(self.to_move);
+                Ok(())
+            }
+            Some(owner) => Err(IllegalMove::Occupied(*owner)),
+        }
+    }
+
+    /// Take back the last move and return the cell that was freed. A mark that vanished with
+    /// the move comes back.
+    pub fn undo(&mut self) -> Option<usize> {
+        let index = self.history.pop()?;
+        self.to_move = self.board[index];
+        self.board[index] = Field::Free;
+        if let Some(oldest) = self.next_to_vanish(self.to_move) {
+            self.board[oldest] = self.to_move;
+        }
+        Some(index)
+    }
+
+    /// Return the cell of the mark of `player` that vanishes with their next move under the
+    /// rules with limited marks, if they already have all their marks on the board.
+    #[must_use]
+    pub fn next_to_vanish(&self, player: Field) -> Option<usize> {
+        if !self.rules.limited {
+            return None;
+        }
+        // cross makes the moves with even indices
+        let first = usize::from(player != Field::Cross);
+        let placed: Vec<usize> = self.history.iter().skip(first).step_by(2).copied().collect();
+        let limit = self.dims.in_a_row;
+        if placed.len() >= limit { Some(placed[placed.len() - limit]) } else { None }
+    }
+
+    /// Return the result of the game or `None` while it is still running.
+    #[must_use]
+    pub fn outcome(&self) -> Option<Outcome> {
+        if let Some(player) = winner(&self.board, &self.dims, &self.rules) {
+            Some(Outcome::Won(player))
+        }
+        // with limited marks, the board never fills up
+        else if cats_game(&self.board) && !self.rules.limited {
+            Some(Outcome::Draw)
+        }
+        else {
+            None
+        }
+    }
+
+    /// Return the cells of the completed line, if there is one.
+    #[must_use]
+    pub fn winning_line(&self) -> Option<Vec<usize>> {
+        winning_line(&self.board, &self.dims)
+    }
+
+    /// Write the game as text: the board size (followed by `misere`, `gravity` and `limited`
+    /// for these rules) in the first line and the moves in cell notation in the second, e.g. `3x3 3` and
+    /// `b2 a1 c3`.
+    #[must_use]
+    pub fn to_text(&self) -> String {
+        let moves: Vec<String> = self.history.iter().map(|cell| self.dims.cell_name(*cell)).collect();
+        let misere = if self.rules.misere { " misere" } else { "" };
+        let gravity = if self.rules.gravity { " gravity" } else { "" };
+        let limited = if self.rules.limited { " limited" } else { "" };
+        format!("{}x{} {}{misere}{gravity}{limited}\n{}\n", self.dims.rows, self.dims.columns, self.dims.in_a_row, moves.join(" "))
+    }
+
+    /// Read a game written by `to_text` and replay its moves.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the problem if the board size or the rules are invalid or a
+    /// move cannot be played.
+    pub fn from_text(text: &str) -> Result<Game, String> {
+        let mut lines = text.lines();
+        let header = lines.next().ok_or("The file is empty")?;
+        let mut rules = Rules::default();
+        let mut size = vec![];
+        for word in header.split_whitespace() {
+            match word {
+                "misere" => rules.misere = true,
+                "gravity" => rules.gravity = true,
+                "limited" => rules.limited = true,
+                _ => size.push(word),
+            }
+        }
+        if rules.gravity && rules.limited {
+            return Err("Gravity cannot be combined with limited marks".to_string());
+        }
+        let size = size.join(" ");
+        let numbers: Vec<usize> = size
+            .split(|c: char| c == 'x' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .map(|part| part.parse::<usize>().map_err(|_| format!("Invalid board size {size:?}")))
+            .collect::<Result<_, _>>()?;
+        let dims = match numbers[..] {
+            [rows, columns, _] if !(1..=MAX_SIDE).contains(&rows) || !(1..=MAX_SIDE).contains(&columns) => {
+                return Err(format!("Invalid board size {size:?}, rows and columns have to be between 1 and {MAX_SIDE}"));
+            }
+            [rows, columns, in_a_row] if in_a_row > 0 && in_a_row <= rows.max(columns) => Dimensions::new(rows, columns, in_a_row),
+            _ => return Err(format!("Invalid board size {size:?}")),
+        };
+        let mut game = Game::with_rules(dims, rules);
+        for name in lines.flat_map(str::split_whitespace) {
+            let cell = dims.cell_index(name).ok_or(format!("Invalid cell {name:?}"))?;
+            game.play(cell).map_err(|err| format!("Move {name} is not allowed: {err}"))?;
+        }
+        Ok(game)
+    }
+}
+
+/// Running tally of the games played in one session.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Score {
+    pub crosses: u32,
+    pub circles: u32,
+    pub draws: u32,
+}
+
+impl Score {
+    /// Count the result of a finished game.
+    pub fn record(&mut self, outcome: Outcome) {
+        match outcome {
+            Outcome::Won(Field::Cross) => self.crosses += 1,
+            Outcome::Won(Field::Circle) => self.circles += 1,
+            Outcome::Won(Field::Free) | Outcome::Draw => self.draws += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_play_alternates_players() {
+        let mut game = Game::new(Dimensions::default());
+        assert_eq!(Ok(()), game.play(4));
+        assert_eq!(Ok(()), game.play(0));
+        assert_eq!(Field::Cross, game.board()[4]);
+        assert_eq!(Field::Circle, game.board()[0]);
+        assert_eq!(Field::Cross, game.to_move());
+    }
+    #[test]
+    fn test_play_rejects_taken_cell() {
+        let mut game = Game::new(Dimensions::default());
+        game.play(4).unwrap();
+        assert_eq!(Err(IllegalMove::Occupied(Field::Cross)), game.play(4));
+        assert_eq!(Err(IllegalMove::OutOfBoard), game.play(9));
+    }
+    #[test]
+    fn test_undo_restores_player() {
+        let mut game = Game::new(Dimensions::default());
+        game.play(4).unwrap();
+        game.play(0).unwrap();
+        assert_eq!(Some(0), game.undo());
+        assert_eq!(Field::Circle, game.to_move());
+        assert_eq!(vec![4], game.history());
+    }
+    #[test]
+    fn test_outcome_win_ends_game() {
+        let mut game = Game::new(Dimensions::default());
+        for cell in [0, 3, 1, 4, 2] {
+            game.play(cell).unwrap();
+        }
+        assert_eq!(Some(Outcome::Won(Field::Cross)), game.outcome());
+        assert_eq!(Err(IllegalMove::GameOver), game.play(8));
+    }
+    #[test]
+    fn test_text_round_trip() {
+        let mut game = Game::new(Dimensions::new(4, 4, 4));
+        for cell in [5, 0, 15] {
+            game.play(cell).unwrap();
+        }
+        assert_eq!("4x4 4\nb2 a1 d4\n", game.to_text());
+        let loaded = Game::from_text(&game.to_text()).unwrap();
+        assert_eq!(game.board(), loaded.board());
+        assert_eq!(game.history(), loaded.history());
+        assert_eq!(Field::Circle, loaded.to_move());
+    }
+    #[test]
+    fn test_misere_completing_player_loses() {
+        let mut game = Game::with_rules(Dimensions::default(), Rules::misere());
+        for cell in [0, 3, 1, 4, 2] {
+            game.play(cell).unwrap();
+        }
+        assert_eq!(Some(Outcome::Won(Field::Circle)), game.outcome());
+        let loaded = Game::from_text(&game.to_text()).unwrap();
+        assert_eq!(Rules::misere(), *loaded.rules());
+        assert_eq!(Some(Outcome::Won(Field::Circle)), loaded.outcome());
+    }
+    #[test]
+    fn test_gravity_drops_marks() {
+        let mut game = Game::with_rules(Dimensions::new(6, 7, 4), Rules::gravity());
+        assert_eq!(Err(IllegalMove::Floating), game.play(10));
+        for cell in [3, 10, 4] {
+            game.play(cell).unwrap();
+        }
+        assert_eq!("6x7 4 gravity\nd1 d2 e1\n", game.to_text());
+        let loaded = Game::from_text(&game.to_text()).unwrap();
+        assert_eq!(Rules::gravity(), *loaded.rules());
+        assert_eq!(game.board(), loaded.board());
+        let mut floating = vec![Field::Free; 9];
+        floating[4] = Field::Cross;
+        assert_eq!(Err(InvalidPosition::Floating), validate_position(&floating, &Dimensions::default(), &Rules::gravity()));
+    }
+    #[test]
+    fn test_limited_marks_vanish() {
+        let mut game = Game::with_rules(Dimensions::default(), Rules::limited());
+        for cell in [0, 3, 1, 4, 8, 6] {
+            game.play(cell).unwrap();
+        }
+        assert_eq!(Some(0), game.next_to_vanish(Field::Cross));
+        assert_eq!(Some(3), game.next_to_vanish(Field::Circle));
+        // the fourth cross removes the one on 0, so 0, 1 and 2 are no line
+        game.play(2).unwrap();
+        assert_eq!(Field::Free, game.board()[0]);
+        assert_eq!(None, game.outcome());
+        assert_eq!(Some(1), game.next_to_vanish(Field::Cross));
+        assert_eq!(Some(2), game.undo());
+        assert_eq!(Field::Cross, game.board()[0]);
+        let loaded = Game::from_text(&game.to_text()).unwrap();
+        assert_eq!(Rules::limited(), *loaded.rules());
+        assert_eq!(game.board(), loaded.board());
+    }
+    #[test]
+    fn test_from_text_rejects_invalid_games() {
+        assert!(Game::from_text("").is_err());
+        assert!(Game::from_text("3x3 4\n").is_err());
+        assert!(Game::from_text("3x3 3\na1 a1\n").is_err());
+        assert!(Game::from_text("3x3 3\nd1\n").is_err());
+        assert_eq!(Err("Move a1 is not allowed: This field is already taken".to_string()), Game::from_text("3x3 3\na1 a1\n").map(|game| game.to_text()));
+    }
+    #[test]
+    fn test_from_text_rejects_empty_boards() {
+        assert!(Game::from_text("0x3 3\n").is_err());
+        assert!(Game::from_text("3x0 3\n").is_err());
+    }
+    #[test]
+    fn test_from_text_rejects_huge_boards() {
+        assert!(Game::from_text("27x3 3\n").is_err());
+        assert!(Game::from_text("3x1000000000 3\n").is_err());
+        assert!(Game::from_text(&format!("{MAX_SIDE}x{MAX_SIDE} 5\n")).is_ok());
+    }
+    #[test]
+    fn test_validate_position() {
+        let dims = Dimensions::default();
+        assert_eq!(Ok(Field::Cross), validate_position(&[Field::Free; 9], &dims, &Rules::default()));
+        assert_eq!(Ok(Field::Circle), validate_position(&[Field::Free, Field::Free, Field::Free, Field::Free, Field::Cross, Field::Free, Field::Free, Field::Free, Field::Free], &dims, &Rules::default()));
+        assert_eq!(Err(InvalidPosition::MarkCount), validate_position(&[Field::Circle, Field::Free, Field::Free, Field::Free, Field::Free, Field::Free, Field::Free, Field::Free, Field::Free], &dims, &Rules::default()));
+        assert_eq!(Err(InvalidPosition::BothWon), validate_position(&[Field::Cross, Field::Cross, Field::Cross, Field::Circle, Field::Circle, Field::Circle, Field::Free, Field::Free, Field::Free], &dims, &Rules::default()));
+        assert_eq!(Err(InvalidPosition::MoveAfterWin(Field::Cross)), validate_position(&[Field::Cross, Field::Cross, Field::Cross, Field::Circle, Field::Circle, Field::Free, Field::Circle, Field::Free, Field::Free], &dims, &Rules::default()));
+        assert_eq!(Err(InvalidPosition::WrongSize), validate_position(&[Field::Free; 4], &dims, &Rules::default()));
+    }
+    #[test]
+    fn test_score_record() {
+        let mut score = Score::default();
+        score.record(Outcome::Won(Field::Circle));
+        score.record(Outcome::Draw);
+        assert_eq!(Score { crosses: 0, circles: 1, draws: 1 }, score);
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use super::{Dimensions, Field, Rules};
+use super::check_functions::{available_moves, winner};
+use super::symmetry::{canonical_key_under, Symmetry};
+
+/// Cache of solved positions keyed by their canonical form.
+///
+/// Positions that only differ by a rotation or reflection share one entry, so every class of
+/// equivalent positions is evaluated only once.
+pub struct TranspositionTable {
+    entries: HashMap<(u32, bool), i32>,
+    /// Symmetries that map positions onto equivalent ones.
+    symmetries: &'static [Symmetry],
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        TranspositionTable::new()
+    }
+}
+
+impl TranspositionTable {
+    #[must_use]
+    pub fn new() -> TranspositionTable {
+        TranspositionTable::with_symmetries(&Symmetry::ALL)
+    }
+
+    /// Create a table that only treats positions as equivalent under the given symmetries,
+    /// e.g. for rules that do not allow to rotate the board.
+    #[must_use]
+    pub fn with_symmetries(symmetries: &'static [Symmetry]) -> TranspositionTable {
+        TranspositionTable { entries: HashMap::new(), symmetries }
+    }
+
+    fn key(&self, board: &[Field], to_move: Field) -> (u32, bool) {
+        (canonical_key_under(board, self.symmetries), to_move == Field::Cross)
+    }
+
+    /// Look up the stored value of a position.
+    #[must_use]
+    pub fn get(&self, board: &[Field], to_move: Field) -> Option<i32> {
+        self.entries.get(&self.key(board, to_move)).copied()
+    }
+
+    /// Store the value of a position (and thereby of all its equivalent positions).
+    pub fn insert(&mut self, board: &[Field], to_move: Field, value: i32) {
+        let key = self.key(board, to_move);
+        self.entries.insert(key, value);
+    }
+
+    /// Number of distinct (canonical) positions stored.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Return the opponent of a player.
+#[must_use]
+pub fn opponent(player: Field) -> Field {
+    match player {
+        Field::Cross => Field::Circle,
+        Field::Circle => Field::Cross,
+        Field::Free => Field::Free,
+    }
+}
+
+/// Perfect-play solver for the 3x3 board.
+///
+/// Values are given from the point of view of the player to move: positive values are wins,
+/// negative values are losses and zero is a draw. Faster wins (and slower losses) have a
+/// larger absolute value.
+#[derive(Default)]
+pub struct Solver {
+    table: TranspositionTable,
+    rules: Rules,
+}
+
+impl Solver {
+    #[must_use]
+    pub fn new() -> Solver {
+        Solver::default()
+    }
+
+    /// Create a solver for other rules, e.g. misère.
+    #[must_use]
+    pub fn with_rules(rules: Rules) -> Solver {
+        // gravity does not allow to turn the board upside down
+        let symmetries: &'static [Symmetry] = if rules.gravity { &Symmetry::MIRRORS } else { &Symmetry::ALL };
+        Solver { table: TranspositionTable::with_symmetries(symmetries), rules }
+    }
+
+    #[must_use]
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// Access the positions solved so far.
+    #[must_use]
+    pub fn table(&self) -> &TranspositionTable {
+        &self.table
+    }
+
+    /// Return the game theoretic value of the position for the player to move.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the board does not have 9 cells.
+    pub fn evaluate(&mut self, board: &[Field], to_move: Field) -> i32 {
+        assert_eq!(Dimensions::default().cells(), board.len(), "the solver plays on the 3x3 board");
+        let mut board = board.to_vec();
+        self.negamax(&mut board, to_move)
+    }
+
+    /// Return the value of every free cell for `to_move`, `None` for occupied cells (and,
+    /// with gravity, for cells above the lowest free cell of their column).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the board does not have 9 cells.
+    pub fn evaluate_moves(&mut self, board: &[Field], to_move: Field) -> Vec<Option<i32>> {
+        assert_eq!(Dimensions::default().cells(), board.len(), "the solver plays on the 3x3 board");
+        let moves = available_moves(board, &Dimensions::default(), &self.rules);
+        let mut board = board.to_vec();
+        (0..board.len())
+            .map(|i| {
+                if !moves.contains(&i) {
+                    return None;
+                }
+                board[i] = to_move;
+                let value = -self.negamax(&mut board, opponent(to_move));
+                board[i] = Field::Free;
+                Some(value)
+            })
+            .collect()
+    }
+
+    /// Return the best move for `to_move`, or `None` if the board is full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the board does not have 9 cells.
+    pub fn best_move(&mut self, board: &[Field], to_move: Field) -> Option<usize> {
+        let mut best: Option<(usize, i32)> = None;
+        for (i, value) in self.evaluate_moves(board, to_move).into_iter().enumerate() {
+            if let Some(value) = value {
+                if best.is_none_or(|(_, best_value)| value > best_value) {
+                    best = Some((i, value));
+                }
+            }
+        }
+        best.map(|(i, _)| i)
+    }
+
+    fn negamax(&mut self, board: &mut Vec<Field>, to_move: Field) -> i32 {
+        if let Some(value) = self.table.get(board, to_move) {
+            return value;
+        }
+        let free = board.iter().filter(|cell| **cell == Field::Free).count();
+        let value = if let Some(winner) = winner(board, &Dimensions::default(), &self.rules) {
+            // the opponent completed a line with the last move, which loses in misère
+            if winner == to_move { free as i32 + 1 } else { -(free as i32 + 1) }
+        }
+        else if free == 0 {
+            0
+        }
+        else {
+            let mut best = i32::MIN;
+            for i in available_moves(board, &Dimensions::default(), &self.rules) {
+                board[i] = to_move;
+                best = best.max(-self.negamax(board, opponent(to_move)));
+                board[i] = Field::Free;
+            }
+            best
+        };
+        self.table.insert(board, to_move, value);
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_board_is_draw() {
+        let mut solver = Solver::new();
+        assert_eq!(0, solver.evaluate(&[Field::Free; 9], Field::Cross));
+    }
+    #[test]
+    fn test_table_uses_symmetry() {
+        let mut solver = Solver::new();
+        solver.evaluate(&[Field::Free; 9], Field::Cross);
+        // 5478 legal positions collapse to 765 classes of equivalent positions
+        assert_eq!(765, solver.table().len());
+    }
+    #[test]
+    fn test_best_move_wins() {
+        let board = vec![Field::Circle, Field::Circle, Field::Free, Field::Cross, Field::Cross, Field::Free, Field::Free, Field::Free, Field::Free];
+        let mut solver = Solver::new();
+        assert_eq!(Some(2), solver.best_move(&board, Field::Circle));
+    }
+    #[test]
+    fn test_best_move_blocks() {
+        let board = vec![Field::Cross, Field::Cross, Field::Free, Field::Free, Field::Circle, Field::Free, Field::Free, Field::Free, Field::Free];
+        let mut solver = Solver::new();
+        assert_eq!(Some(2), solver.best_move(&board, Field::Circle));
+    }
+    #[test]
+    fn test_misere_is_draw() {
+        let mut solver = Solver::with_rules(Rules::misere());
+        assert_eq!(0, solver.evaluate(&[Field::Free; 9], Field::Cross));
+    }
+    #[test]
+    fn test_misere_avoids_line() {
+        let board = vec![Field::Circle, Field::Circle, Field::Free, Field::Cross, Field::Cross, Field::Free, Field::Free, Field::Free, Field::Free];
+        let mut solver = Solver::with_rules(Rules::misere());
+        assert_ne!(Some(2), solver.best_move(&board, Field::Circle));
+    }
+    #[test]
+    fn test_gravity_only_drops() {
+        let mut board = vec![Field::Free; 9];
+        board[1] = Field::Cross;
+        let mut solver = Solver::with_rules(Rules::gravity());
+        let values = solver.evaluate_moves(&board, Field::Circle);
+        let moves: Vec<usize> = (0..9).filter(|i| values[*i].is_some()).collect();
+        assert_eq!(vec![0, 2, 4], moves);
+    }
+    #[test]
+    fn test_equivalent_positions_share_entry() {
+        let mut board = vec![Field::Free; 9];
+        board[0] = Field::Cross;
+        let mut table = TranspositionTable::new();
+        table.insert(&board, Field::Circle, 0);
+        let mut rotated = vec![Field::Free; 9];
+        rotated[8] = Field::Cross;
+        assert_eq!(Some(0), table.get(&rotated, Field::Circle));
+        assert_eq!(None, table.get(&rotated, Field::Cross));
+    }
+    #[test]
+    #[should_panic(expected = "the solver plays on the 3x3 board")]
+    fn test_evaluate_rejects_other_sizes() {
+        Solver::new().evaluate(&[Field::Free; 16], Field::Cross);
+    }
+    #[test]
+    #[should_panic(expected = "the solver plays on the 3x3 board")]
+    fn test_evaluate_moves_rejects_other_sizes() {
+        Solver::new().evaluate_moves(&[Field::Free; 4], Field::Cross);
+    }
+}
@@ -0,0 +1,225 @@
+use super::{CellContent, Field};
+
+/// Side length of the board the symmetries act on.
+const SIDE: usize = 3;
+
+/// One of the eight symmetries of the square board (the dihedral group of order 8).
+///
+/// The board vector starts with the bottom row, so index 0 is `a1` in the bottom-left corner
+/// like everywhere else. Rotations turn the board counter-clockwise as it is shown, e.g.
+/// `Rotate90` moves `a1` to `c1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// Mirror at the horizontal axis (top and bottom row are swapped).
+    ReflectHorizontal,
+    /// Mirror at the vertical axis (left and right column are swapped).
+    ReflectVertical,
+    /// Mirror at the main diagonal (0, 4, 8).
+    ReflectDiagonal,
+    /// Mirror at the anti-diagonal (2, 4, 6).
+    ReflectAntiDiagonal,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+        Symmetry::ReflectHorizontal,
+        Symmetry::ReflectVertical,
+        Symmetry::ReflectDiagonal,
+        Symmetry::ReflectAntiDiagonal,
+    ];
+
+    /// Symmetries that keep the bottom row at the bottom, the only ones left when marks fall
+    /// down the columns.
+    pub const MIRRORS: [Symmetry; 2] = [Symmetry::Identity, Symmetry::ReflectVertical];
+
+    /// Return the symmetry that undoes this one.
+    #[must_use]
+    pub fn inverse(self) -> Symmetry {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            other => other,
+        }
+    }
+
+    /// Return the cell the content of `index` is moved to by this symmetry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is not a cell of the 3x3 board.
+    #[must_use]
+    pub fn apply_index(self, index: usize) -> usize {
+        assert!(index < SIDE * SIDE, "cell {index} is not on the 3x3 board");
+        let last = SIDE - 1;
+        let (row, col) = (index / SIDE, index % SIDE);
+        let (new_row, new_col) = match self {
+            Symmetry::Identity => (row, col),
+            Symmetry::Rotate90 => (col, last - row),
+            Symmetry::Rotate180 => (last - row, last - col),
+            Symmetry::Rotate270 => (last - col, row),
+            Symmetry::ReflectHorizontal => (last - row, col),
+            Symmetry::ReflectVertical => (row, last - col),
+            Symmetry::ReflectDiagonal => (col, row),
+            Symmetry::ReflectAntiDiagonal => (last - col, last - row),
+        };
+        new_row * SIDE + new_col
+    }
+
+    /// Map a move made on the transformed board back to the board the symmetry was applied to.
+    #[must_use]
+    pub fn original_index(self, index: usize) -> usize {
+        self.inverse().apply_index(index)
+    }
+
+    /// Return a transformed copy of the board.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the board does not have 9 cells.
+    #[must_use]
+    pub fn apply<C: CellContent>(self, board: &[C]) -> Vec<C> {
+        assert_eq!(SIDE * SIDE, board.len(), "symmetries act on the 3x3 board");
+        let mut transformed = vec![C::FREE; board.len()];
+        for (i, cell) in board.iter().enumerate() {
+            transformed[self.apply_index(i)] = *cell;
+        }
+        transformed
+    }
+}
+
+/// Encode a board as a base-3 number (free = 0, cross = 1, circle = 2).
+#[must_use]
+pub fn encode(board: &[Field]) -> u32 {
+    board.iter().rev().fold(0, |key, cell| {
+        key * 3 + match cell {
+            Field::Free => 0,
+            Field::Cross => 1,
+            Field::Circle => 2,
+        }
+    })
+}
+
+/// Return the canonical representative of the board together with the symmetry that maps
+/// the given board onto it.
+///
+/// All eight boards that are equivalent under rotation and reflection have the same canonical
+/// representative, which is the one with the smallest encoding.
+#[must_use]
+pub fn canonical(board: &[Field]) -> (Vec<Field>, Symmetry) {
+    let mut best = (board.to_vec(), Symmetry::Identity);
+    let mut best_key = encode(board);
+    for symmetry in &Symmetry::ALL[1..] {
+        let transformed = symmetry.apply(board);
+        let key = encode(&transformed);
+        if key < best_key {
+            best_key = key;
+            best = (transformed, *symmetry);
+        }
+    }
+    best
+}
+
+/// Return the encoding of the canonical representative of the board.
+#[must_use]
+pub fn canonical_key(board: &[Field]) -> u32 {
+    canonical_key_under(board, &Symmetry::ALL)
+}
+
+/// Return the smallest encoding of the board under the given symmetries.
+#[must_use]
+pub fn canonical_key_under(board: &[Field], symmetries: &[Symmetry]) -> u32 {
+    symmetries.iter().map(|symmetry| encode(&symmetry.apply(board))).min().unwrap_or(0)
+}
+
+/// Group the free cells of the board into classes of moves that lead to equivalent positions.
+///
+/// The classes are ordered by their smallest cell and every class is sorted, so the first entry
+/// of each class can be used as its representative. On the empty board this yields the center,
+/// the corners and the edges.
+#[must_use]
+pub fn equivalent_moves(board: &[Field]) -> Vec<Vec<usize>> {
+    let stabilizer: Vec<Symmetry> = Symmetry::ALL
+        .iter()
+        .copied()
+        .filter(|symmetry| symmetry.apply(board) == board)
+        .collect();
+    let mut classes: Vec<Vec<usize>> = vec![];
+    for (i, cell) in board.iter().enumerate() {
+        if *cell != Field::Free || classes.iter().any(|class| class.contains(&i)) {
+            continue;
+        }
+        let mut class: Vec<usize> = stabilizer.iter().map(|symmetry| symmetry.apply_index(i)).collect();
+        class.sort_unstable();
+        class.dedup();
+        classes.push(class);
+    }
+    classes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotate_corner() {
+        assert_eq!(2, Symmetry::Rotate90.apply_index(0));
+        assert_eq!(8, Symmetry::Rotate180.apply_index(0));
+        assert_eq!(6, Symmetry::Rotate270.apply_index(0));
+    }
+    #[test]
+    fn test_inverse_maps_back() {
+        for symmetry in Symmetry::ALL {
+            for i in 0..9 {
+                assert_eq!(i, symmetry.original_index(symmetry.apply_index(i)));
+            }
+        }
+    }
+    #[test]
+    fn test_canonical_of_corner_openings_is_equal() {
+        let mut keys = vec![];
+        for corner in [0, 2, 6, 8] {
+            let mut board = vec![Field::Free; 9];
+            board[corner] = Field::Cross;
+            keys.push(canonical_key(&board));
+        }
+        keys.dedup();
+        assert_eq!(1, keys.len());
+    }
+    #[test]
+    fn test_canonical_maps_board_onto_representative() {
+        let board = vec![Field::Free, Field::Free, Field::Cross, Field::Free, Field::Circle, Field::Free, Field::Free, Field::Free, Field::Free];
+        let (representative, symmetry) = canonical(&board);
+        assert_eq!(representative, symmetry.apply(&board));
+        assert_eq!(encode(&representative), canonical_key(&board));
+    }
+    #[test]
+    fn test_equivalent_moves_empty_board() {
+        let board = vec![Field::Free; 9];
+        let classes = equivalent_moves(&board);
+        assert_eq!(vec![vec![0, 2, 6, 8], vec![1, 3, 5, 7], vec![4]], classes);
+    }
+    #[test]
+    fn test_equivalent_moves_after_center() {
+        let mut board = vec![Field::Free; 9];
+        board[4] = Field::Cross;
+        assert_eq!(2, equivalent_moves(&board).len());
+    }
+    #[test]
+    #[should_panic(expected = "symmetries act on the 3x3 board")]
+    fn test_apply_rejects_other_sizes() {
+        let _ = Symmetry::Rotate90.apply(&[Field::Free; 16]);
+    }
+    #[test]
+    #[should_panic(expected = "cell 9 is not on the 3x3 board")]
+    fn test_apply_index_rejects_cells_off_the_board() {
+        let _ = Symmetry::Rotate90.apply_index(9);
+    }
+}
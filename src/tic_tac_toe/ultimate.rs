@@ -0,0 +1,281 @@
+use super::{Dimensions, Field};
+use super::check_functions::{cats_game, someone_has_won, winning_line};
+use super::session::{IllegalMove, Outcome};
+use super::solver::opponent;
+use super::variant::{Variant, WIN};
+
+/// Number of small boards and of cells on each of them.
+pub const SIDE: usize = 9;
+
+/// Value of the lines of the big board for the search, by the number of boards won in them.
+const META_LINE_VALUES: [i32; 3] = [0, 40, 300];
+/// Value of the lines of a small board, by the number of marks in them.
+const BOARD_LINE_VALUES: [i32; 3] = [0, 1, 6];
+/// Value of a won small board, the center board counts more.
+const BOARD_VALUE: i32 = 60;
+
+/// Ultimate tic-tac-toe: a 3x3 grid of 3x3 boards.
+///
+/// The cell of a move picks the board the opponent has to play on next. If that board is
+/// already decided, the opponent may play on any open board. Whoever wins three boards in a
+/// row wins the game.
+///
+/// Move `m` takes cell `m % 9` of board `m / 9`; boards and cells are numbered like the cells
+/// of the classic board.
+#[derive(Clone, Debug)]
+pub struct Ultimate {
+    boards: Vec<Vec<Field>>,
+    /// Result of every small board, `None` while it is open.
+    results: Vec<Option<Outcome>>,
+    /// Board the next move has to be made on, `None` for a free choice.
+    active: Option<usize>,
+    to_move: Field,
+    history: Vec<usize>,
+    /// Active board before each move, to take moves back.
+    previous_active: Vec<Option<usize>>,
+}
+
+impl Default for Ultimate {
+    fn default() -> Self {
+        Ultimate::new()
+    }
+}
+
+impl Ultimate {
+    #[must_use]
+    pub fn new() -> Ultimate {
+        Ultimate {
+            boards: vec![vec![Field::Free; SIDE]; SIDE],
+            results: vec![None; SIDE],
+            active: None,
+            to_move: Field::Cross,
+            history: vec![],
+            previous_active: vec![],
+        }
+    }
+
+    #[must_use]
+    pub fn board(&self, index: usize) -> &[Field] {
+        &self.boards[index]
+    }
+
+    /// Return the result of a small board, `None` while it is open.
+    #[must_use]
+    pub fn result(&self, index: usize) -> Option<Outcome> {
+        self.results[index]
+    }
+
+    /// Return the board the next move has to be made on, `None` for a free choice.
+    #[must_use]
+    pub fn active(&self) -> Option<usize> {
+        self.active
+    }
+
+    /// Whether the next move may be made on the given board.
+    #[must_use]
+    pub fn is_playable(&self, index: usize) -> bool {
+        self.outcome().is_none() && self.results[index].is_none() && self.active.is_none_or(|active| active == index)
+    }
+
+    /// The big board with the winners of the small boards. Drawn and open boards are free.
+    #[must_use]
+    pub fn meta_board(&self) -> Vec<Field> {
+        self.results
+            .iter()
+            .map(|result| match result {
+                Some(Outcome::Won(player)) => *player,
+                _ => Field::Free,
+            })
+            .collect()
+    }
+
+    /// Return the boards of the line that won the game, if there is one.
+    #[must_use]
+    pub fn winning_line(&self) -> Option<Vec<usize>> {
+        winning_line(&self.meta_board(), &Dimensions::default())
+    }
+
+    /// Return the cells of the line that won a small board.
+    #[must_use]
+    pub fn board_line(&self, index: usize) -> Option<Vec<usize>> {
+        winning_line(&self.boards[index], &Dimensions::default())
+    }
+
+    fn board_result(board: &[Field]) -> Option<Outcome> {
+        match someone_has_won(board, &Dimensions::default()) {
+            (true, player) => Some(Outcome::Won(player)),
+            _ if cats_game(board) => Some(Outcome::Draw),
+            _ => None,
+        }
+    }
+
+    /// Sum of the values of the lines of `board` that only `player` has marks in, minus the
+    /// same for the opponent.
+    fn line_value(board: &[Field], player: Field, values: &[i32]) -> i32 {
+        let other = opponent(player);
+        Dimensions::default()
+            .lines()
+            .iter()
+            .map(|line| {
+                let own = line.iter().filter(|cell| board[**cell] == player).count();
+                let theirs = line.iter().filter(|cell| board[**cell] == other).count();
+                match (own, theirs) {
+                    (own, 0) if own < values.len() => values[own],
+                    (0, theirs) if theirs < values.len() => -values[theirs],
+                    _ => 0,
+                }
+            })
+            .sum()
+    }
+}
+
+impl Variant for Ultimate {
+    fn name(&self) -> String {
+        "Ultimate tic-tac-toe".to_string()
+    }
+
+    fn restart(&self) -> Self {
+        Ultimate::new()
+    }
+
+    fn to_move(&self) -> Field {
+        self.to_move
+    }
+
+    fn history(&self) -> &[usize] {
+        &self.history
+    }
+
+    fn legal_moves(&self) -> Vec<usize> {
+        (0..SIDE)
+            .filter(|board| self.is_playable(*board))
+            .flat_map(|board| (0..SIDE).filter(move |cell| self.boards[board][*cell] == Field::Free).map(move |cell| board * SIDE + cell))
+            .collect()
+    }
+
+    fn play(&mut self, mv: usize) -> Result<(), IllegalMove> {
+        if self.outcome().is_some() {
+            return Err(IllegalMove::GameOver);
+        }
+        let (board, cell) = (mv / SIDE, mv % SIDE);
+        if board >= SIDE {
+            return Err(IllegalMove::OutOfBoard);
+        }
+        if !self.is_playable(board) {
+            return Err(IllegalMove::WrongBoard);
+        }
+        if self.boards[board][cell] != Field::Free {
+            return Err(IllegalMove::Occupied(self.boards[board][cell]));
+        }
+        self.boards[board][cell] = self.to_move;
+        self.results[board] = Ultimate::board_result(&self.boards[board]);
+        self.previous_active.push(self.active);
+        self.history.push(mv);
+        self.active = if self.results[cell].is_none() { Some(cell) } else { None };
+        self.to_move = opponent(self.to_move);
+        Ok(())
+    }
+
+    fn undo(&mut self) -> Option<usize> {
+        let mv = self.history.pop()?;
+        let (board, cell) = (mv / SIDE, mv % SIDE);
+        self.to_move = self.boards[board][cell];
+        self.boards[board][cell] = Field::Free;
+        // moves are only made on open boards
+        self.results[board] = None;
+        self.active = self.previous_active.pop().flatten();
+        Some(mv)
+    }
+
+    fn outcome(&self) -> Option<Outcome> {
+        match someone_has_won(&self.meta_board(), &Dimensions::default()) {
+            (true, player) => Some(Outcome::Won(player)),
+            _ if self.results.iter().all(Option::is_some) => Some(Outcome::Draw),
+            _ => None,
+        }
+    }
+
+    fn move_name(&self, mv: usize) -> String {
+        let dims = Dimensions::default();
+        format!("{}:{}", dims.cell_name(mv / SIDE), dims.cell_name(mv % SIDE))
+    }
+
+    fn evaluate(&self, player: Field) -> i32 {
+        let meta = self.meta_board();
+        let mut value = Ultimate::line_value(&meta, player, &META_LINE_VALUES);
+        for (index, result) in self.results.iter().enumerate() {
+            let weight = if index == Dimensions::default().center() { 2 } else { 1 };
+            value += weight
+                * match result {
+                    Some(Outcome::Won(winner)) if *winner == player => BOARD_VALUE,
+                    Some(Outcome::Won(_)) => -BOARD_VALUE,
+                    Some(Outcome::Draw) => 0,
+                    None => Ultimate::line_value(&self.boards[index], player, &BOARD_LINE_VALUES),
+                };
+        }
+        // a free choice for the opponent is a disadvantage
+        if self.active.is_none() {
+            value += if self.to_move == player { BOARD_LINE_VALUES[2] } else { -BOARD_LINE_VALUES[2] };
+        }
+        value.clamp(-WIN / 2, WIN / 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_move_sends_to_board() {
+        let mut game = Ultimate::new();
+        assert_eq!(81, game.legal_moves().len());
+        game.play(4 * SIDE + 2).unwrap();
+        assert_eq!(Some(2), game.active());
+        assert_eq!(Err(IllegalMove::WrongBoard), game.play(4 * SIDE + 3));
+        assert_eq!(Ok(()), game.play(2 * SIDE + 4));
+    }
+    /// Cross is about to win board 0 with cells 0, 1 and 2 and has to play there.
+    fn almost_won_board() -> Ultimate {
+        let mut game = Ultimate::new();
+        game.boards[0] = vec![Field::Cross, Field::Cross, Field::Free, Field::Circle, Field::Circle, Field::Free, Field::Free, Field::Free, Field::Free];
+        game.active = Some(0);
+        game
+    }
+    #[test]
+    fn test_decided_board_gives_free_choice() {
+        let mut game = almost_won_board();
+        game.play(2).unwrap();
+        assert_eq!(Some(Outcome::Won(Field::Cross)), game.result(0));
+        // circle sends cross to the decided board 0, so cross may play anywhere
+        game.play(2 * SIDE).unwrap();
+        assert_eq!(None, game.active());
+        assert_eq!(Err(IllegalMove::WrongBoard), game.play(5));
+        assert!(game.legal_moves().iter().all(|mv| mv / SIDE != 0));
+    }
+    #[test]
+    fn test_undo_restores_state() {
+        let mut game = almost_won_board();
+        game.play(2).unwrap();
+        assert_eq!(Some(2), game.undo());
+        assert_eq!(None, game.result(0));
+        assert_eq!(Some(0), game.active());
+        assert_eq!(Field::Cross, game.to_move());
+    }
+    #[test]
+    fn test_meta_line_wins() {
+        let mut game = Ultimate::new();
+        game.results = vec![Some(Outcome::Won(Field::Circle)), Some(Outcome::Won(Field::Circle)), Some(Outcome::Won(Field::Circle)), None, None, None, None, None, None];
+        assert_eq!(Some(Outcome::Won(Field::Circle)), game.outcome());
+        assert_eq!(Some(vec![0, 1, 2]), game.winning_line());
+    }
+    #[test]
+    fn test_ai_completes_board_line() {
+        let mut game = Ultimate::new();
+        // cross has two winning boards and can win board 2 and thereby the game
+        game.results = vec![Some(Outcome::Won(Field::Cross)), Some(Outcome::Won(Field::Cross)), None, None, None, None, None, None, None];
+        game.boards[2] = vec![Field::Cross, Field::Cross, Field::Free, Field::Circle, Field::Circle, Field::Free, Field::Free, Field::Free, Field::Free];
+        game.active = Some(2);
+        assert_eq!(Some(2 * SIDE + 2), game.ai_move(2, Duration::from_millis(200)));
+    }
+}
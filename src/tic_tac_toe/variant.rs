@@ -0,0 +1,319 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+extern crate rand;
+use rand::Rng;
+use super::{Field, Message, VariantMessage};
+use super::gomoku::GomokuRule;
+use super::session::{IllegalMove, Outcome};
+
+/// Value of a won position in the search. Wins found sooner get a slightly larger value.
+pub const WIN: i32 = 1_000_000;
+
+/// Variants that are played in a window of their own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VariantKind {
+    Ultimate,
+    Qubic,
+    Gomoku(GomokuRule),
+    Morris,
+    Wild,
+    /// Notakto on the given number of boards.
+    Notakto(usize),
+    Numerical,
+}
+
+/// Windows of variants by their id, which their messages carry to tell them apart.
+///
+/// Ids are never reused, so messages that arrive after their window was closed (e.g. the
+/// answer of the AI) are dropped instead of reaching another window.
+pub struct WindowList<W> {
+    windows: Vec<(usize, W)>,
+    next_id: usize,
+}
+
+impl<W> Default for WindowList<W> {
+    fn default() -> Self {
+        WindowList { windows: vec![], next_id: 0 }
+    }
+}
+
+impl<W> WindowList<W> {
+    /// Add the window `open` creates for the next id and return the id.
+    pub fn open(&mut self, open: impl FnOnce(usize) -> W) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.windows.push((id, open(id)));
+        id
+    }
+
+    /// Return the window with the given id, `None` if it was closed.
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut W> {
+        self.windows.iter_mut().find(|(window_id, _)| *window_id == id).map(|(_, window)| window)
+    }
+
+    /// Keep only the windows for which `keep` returns `true`.
+    pub fn retain(&mut self, mut keep: impl FnMut(&mut W) -> bool) {
+        self.windows.retain_mut(|(_, window)| keep(window));
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.windows.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+}
+
+impl<W: MessageTarget> WindowList<W> {
+    /// Pass a `Message::Variant` on to its window, or drop it if the window was closed. Every
+    /// other message is meant for the main window and returned.
+    pub fn dispatch(&mut self, message: Message) -> Option<Message> {
+        match message {
+            Message::Variant(id, message) => {
+                if let Some(window) = self.get_mut(id) {
+                    window.handle(message);
+                }
+                None
+            }
+            other => Some(other),
+        }
+    }
+}
+
+/// Window of a `WindowList` that handles the messages addressed to it.
+pub trait MessageTarget {
+    fn handle(&mut self, message: VariantMessage);
+}
+
+impl<T: MessageTarget + ?Sized> MessageTarget for Box<T> {
+    fn handle(&mut self, message: VariantMessage) {
+        (**self).handle(message);
+    }
+}
+
+/// A two-player game that is played in a window of its own.
+///
+/// The players are told apart as `Field::Cross` (who moves first) and `Field::Circle`, even if
+/// they place other marks. Moves are numbered by the variant, usually by the cell they take.
+pub trait Variant: Clone + Send + 'static {
+    /// Name shown in the title of the window.
+    fn name(&self) -> String;
+
+    /// Return a new game with the same settings.
+    #[must_use]
+    fn restart(&self) -> Self;
+
+    fn to_move(&self) -> Field;
+
+    /// Name of the marks of `player` in the status panel.
+    fn player_name(&self, player: Field) -> String {
+        match player {
+            Field::Cross => "X".to_string(),
+            _ => "O".to_string(),
+        }
+    }
+
+    /// Moves in the order they were made.
+    fn history(&self) -> &[usize];
+
+    fn legal_moves(&self) -> Vec<usize>;
+
+    /// Make a move for the player to move.
+    ///
+    /// # Errors
+    ///
+    /// Returns why the move is not allowed.
+    fn play(&mut self, mv: usize) -> Result<(), IllegalMove>;
+
+    /// Take back the last move and return it.
+    fn undo(&mut self) -> Option<usize>;
+
+    /// Return the result of the game or `None` while it is still running.
+    fn outcome(&self) -> Option<Outcome>;
+
+    /// Name of a move as shown to the players, e.g. `b2`.
+    fn move_name(&self, mv: usize) -> String;
+
+    /// Heuristic value of a running game for `player`, used where the search stops.
+    fn evaluate(&self, player: Field) -> i32;
+
+    /// Moves the search looks at, by default all legal moves.
+    fn candidate_moves(&self) -> Vec<usize> {
+        self.legal_moves()
+    }
+
+    /// Key of the position for games that can repeat positions, `None` (the default) for
+    /// games that cannot. The search scores a position that already occurred as a draw.
+    fn position_key(&self) -> Option<Vec<usize>> {
+        None
+    }
+
+    /// Choose a move for the player to move with the given difficulty (0 to 2), thinking at
+    /// most `think_time`.
+    fn ai_move(&self, difficulty: i32, think_time: Duration) -> Option<usize> {
+        search_move(self, difficulty, think_time)
+    }
+}
+
+/// Chance that the AI plays a random move instead of the move it thinks best, by difficulty.
+#[must_use]
+pub fn random_move_chance(difficulty: i32) -> f64 {
+    match difficulty {
+        0 => 0.5,
+        1 => 0.2,
+        _ => 0.0,
+    }
+}
+
+/// Choose a move by a game tree search. Lower difficulties search less deeply and sometimes
+/// play a random move, the hardest one deepens the search until the think time is over.
+///
+/// In games that can repeat positions, a position that already occurred (in the game or
+/// earlier in the search) is scored as a draw, since the defending side can repeat it forever.
+pub fn search_move<G: Variant>(game: &G, difficulty: i32, think_time: Duration) -> Option<usize> {
+    let moves = game.legal_moves();
+    if moves.is_empty() {
+        return None;
+    }
+    let mut rng = rand::thread_rng();
+    let max_depth = match difficulty {
+        0 => 1,
+        1 => 2,
+        _ => usize::MAX,
+    };
+    if rng.gen_bool(random_move_chance(difficulty)) {
+        return Some(moves[rng.gen_range(0..moves.len())]);
+    }
+    let deadline = Instant::now() + think_time;
+    // every position of the game so far counts as a repetition
+    let mut seen = HashSet::new();
+    let mut replay = game.clone();
+    seen.extend(replay.position_key());
+    while replay.undo().is_some() {
+        seen.extend(replay.position_key());
+    }
+    let mut best = None;
+    let mut game = game.clone();
+    let mut depth = 1;
+    while depth <= max_depth {
+        match search_root(&mut game, depth, &mut seen, deadline) {
+            Some((mv, value)) => {
+                best = Some(mv);
+                // nothing changes once the result is known
+                if value.abs() >= WIN {
+                    break;
+                }
+            }
+            None => break,
+        }
+        depth += 1;
+    }
+    // without the time for a single complete search, any candidate is better than none
+    best.or_else(|| game.candidate_moves().first().copied()).or(Some(moves[0]))
+}
+
+/// Search all candidate moves `depth` moves deep. Returns the best move and its value or
+/// `None` if the deadline passed.
+fn search_root<G: Variant>(game: &mut G, depth: usize, seen: &mut HashSet<Vec<usize>>, deadline: Instant) -> Option<(usize, i32)> {
+    let mut best: Option<(usize, i32)> = None;
+    let mut alpha = -WIN * 2;
+    for mv in game.candidate_moves() {
+        if game.play(mv).is_err() {
+            continue;
+        }
+        let value = negamax(game, depth - 1, -WIN * 2, -alpha, seen, deadline);
+        game.undo();
+        let value = -value?;
+        if best.is_none_or(|(_, best_value)| value > best_value) {
+            best = Some((mv, value));
+            alpha = alpha.max(value);
+        }
+    }
+    best
+}
+
+/// Alpha-beta search from the point of view of the player to move.
+fn negamax<G: Variant>(game: &mut G, depth: usize, alpha: i32, beta: i32, seen: &mut HashSet<Vec<usize>>, deadline: Instant) -> Option<i32> {
+    if Instant::now() > deadline {
+        return None;
+    }
+    let player = game.to_move();
+    match game.outcome() {
+        // prefer faster wins and slower losses
+        Some(Outcome::Won(winner)) if winner == player => return Some(WIN + depth as i32),
+        Some(Outcome::Won(_)) => return Some(-WIN - depth as i32),
+        Some(Outcome::Draw) => return Some(0),
+        None => {}
+    }
+    let key = game.position_key();
+    if key.as_ref().is_some_and(|key| seen.contains(key)) {
+        return Some(0);
+    }
+    if depth == 0 {
+        return Some(game.evaluate(player));
+    }
+    // the position counts as a repetition further down the search
+    if let Some(key) = &key {
+        seen.insert(key.clone());
+    }
+    let value = search_moves(game, depth, alpha, beta, seen, deadline);
+    if let Some(key) = &key {
+        seen.remove(key);
+    }
+    value
+}
+
+/// Search the candidate moves of a running game for `negamax`.
+fn search_moves<G: Variant>(game: &mut G, depth: usize, mut alpha: i32, beta: i32, seen: &mut HashSet<Vec<usize>>, deadline: Instant) -> Option<i32> {
+    let mut best = -WIN * 2;
+    for mv in game.candidate_moves() {
+        if game.play(mv).is_err() {
+            continue;
+        }
+        let value = negamax(game, depth - 1, -beta, -alpha, seen, deadline);
+        game.undo();
+        best = best.max(-value?);
+        alpha = alpha.max(best);
+        if alpha >= beta {
+            break;
+        }
+    }
+    Some(best)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A window that keeps the messages it got.
+    impl MessageTarget for Vec<VariantMessage> {
+        fn handle(&mut self, message: VariantMessage) {
+            self.push(message);
+        }
+    }
+
+    #[test]
+    fn test_messages_reach_their_window() {
+        let mut windows: WindowList<Vec<VariantMessage>> = WindowList::default();
+        let first = windows.open(|_| vec![]);
+        let second = windows.open(|_| vec![]);
+        assert_ne!(first, second);
+        assert!(windows.dispatch(Message::Variant(second, VariantMessage::Number(4))).is_none());
+        assert!(windows.dispatch(Message::Variant(first, VariantMessage::AiMove(0, 7))).is_none());
+        assert!(windows.dispatch(Message::Variant(second, VariantMessage::Close)).is_none());
+        // messages of the main window are returned to it
+        assert!(matches!(windows.dispatch(Message::Number(2)), Some(Message::Number(2))));
+        assert_eq!(Some(&mut vec![VariantMessage::AiMove(0, 7)]), windows.get_mut(first));
+        assert_eq!(Some(&mut vec![VariantMessage::Number(4), VariantMessage::Close]), windows.get_mut(second));
+        // a closed window gets nothing, and its id is not given to the next one
+        windows.retain(|messages| !messages.contains(&VariantMessage::Close));
+        let third = windows.open(|_| vec![]);
+        assert!(windows.dispatch(Message::Variant(second, VariantMessage::AiTimeout(0))).is_none());
+        assert_eq!(None, windows.get_mut(second));
+        assert_eq!(Some(&mut vec![]), windows.get_mut(third));
+        assert_eq!(2, windows.len());
+    }
+}
@@ -0,0 +1,225 @@
+use super::{Dimensions, Field};
+use super::check_functions::lines_3x3;
+use super::session::{IllegalMove, Outcome};
+use super::solver::opponent;
+use super::variant::{Variant, WIN};
+
+/// Number of cells of the board.
+pub const CELLS: usize = 9;
+
+/// Wild tic-tac-toe on the 3x3 board: every move places a cross or a circle, whichever the
+/// player likes, and whoever completes a line of three equal marks wins.
+///
+/// Move `m` places a cross on cell `m` if it is below `CELLS` and a circle on cell
+/// `m - CELLS` otherwise, see `wild_move`.
+#[derive(Clone, Debug)]
+pub struct Wild {
+    board: Vec<Field>,
+    to_move: Field,
+    history: Vec<usize>,
+}
+
+impl Default for Wild {
+    fn default() -> Self {
+        Wild::new()
+    }
+}
+
+/// Return the move placing `mark` on `cell`.
+#[must_use]
+pub fn wild_move(cell: usize, mark: Field) -> usize {
+    if mark == Field::Circle { CELLS + cell } else { cell }
+}
+
+/// Return the cell and the mark of a move.
+#[must_use]
+pub fn move_mark(mv: usize) -> (usize, Field) {
+    if mv < CELLS { (mv, Field::Cross) } else { (mv - CELLS, Field::Circle) }
+}
+
+impl Wild {
+    #[must_use]
+    pub fn new() -> Wild {
+        Wild { board: vec![Field::Free; CELLS], to_move: Field::Cross, history: vec![] }
+    }
+
+    #[must_use]
+    pub fn board(&self) -> &[Field] {
+        &self.board
+    }
+
+    /// Return the cells of the line that won the game, if there is one.
+    #[must_use]
+    pub fn winning_line(&self) -> Option<Vec<usize>> {
+        lines_3x3()
+            .iter()
+            .find(|line| self.board[line[0]] != Field::Free && line.iter().all(|cell| self.board[*cell] == self.board[line[0]]))
+            .cloned()
+    }
+
+    /// Return the moves that complete a line: two equal marks and a free cell.
+    fn completing_moves(&self) -> Vec<usize> {
+        let mut moves: Vec<usize> = lines_3x3()
+            .iter()
+            .filter_map(|line| {
+                let free: Vec<usize> = line.iter().copied().filter(|cell| self.board[*cell] == Field::Free).collect();
+                let marks: Vec<Field> = line.iter().map(|cell| self.board[*cell]).filter(|mark| *mark != Field::Free).collect();
+                match (free.as_slice(), marks.as_slice()) {
+                    (&[cell], &[first, second]) if first == second => Some(wild_move(cell, first)),
+                    _ => None,
+                }
+            })
+            .collect();
+        moves.sort_unstable();
+        moves.dedup();
+        moves
+    }
+
+    /// Return the moves that do not leave a line for the opponent to complete.
+    fn safe_moves(&self) -> Vec<usize> {
+        let mut game = self.clone();
+        self.legal_moves()
+            .into_iter()
+            .filter(|mv| {
+                let (cell, mark) = move_mark(*mv);
+                game.board[cell] = mark;
+                let safe = game.completing_moves().is_empty();
+                game.board[cell] = Field::Free;
+                safe
+            })
+            .collect()
+    }
+}
+
+impl Variant for Wild {
+    fn name(&self) -> String {
+        "Wild tic-tac-toe".to_string()
+    }
+
+    fn restart(&self) -> Self {
+        Wild::new()
+    }
+
+    fn to_move(&self) -> Field {
+        self.to_move
+    }
+
+    /// Both players place both marks, so they are told apart by their turn.
+    fn player_name(&self, player: Field) -> String {
+        match player {
+            Field::Cross => "Player 1".to_string(),
+            _ => "Player 2".to_string(),
+        }
+    }
+
+    fn history(&self) -> &[usize] {
+        &self.history
+    }
+
+    fn legal_moves(&self) -> Vec<usize> {
+        if self.outcome().is_some() {
+            return vec![];
+        }
+        let free: Vec<usize> = (0..CELLS).filter(|cell| self.board[*cell] == Field::Free).collect();
+        [Field::Cross, Field::Circle].iter().flat_map(|mark| free.iter().map(|cell| wild_move(*cell, *mark))).collect()
+    }
+
+    fn play(&mut self, mv: usize) -> Result<(), IllegalMove> {
+        if self.outcome().is_some() {
+            return Err(IllegalMove::GameOver);
+        }
+        if mv >= 2 * CELLS {
+            return Err(IllegalMove::OutOfBoard);
+        }
+        let (cell, mark) = move_mark(mv);
+        if self.board[cell] != Field::Free {
+            return Err(IllegalMove::Occupied(self.board[cell]));
+        }
+        self.board[cell] = mark;
+        self.history.push(mv);
+        self.to_move = opponent(self.to_move);
+        Ok(())
+    }
+
+    fn undo(&mut self) -> Option<usize> {
+        let mv = self.history.pop()?;
+        self.board[move_mark(mv).0] = Field::Free;
+        self.to_move = opponent(self.to_move);
+        Some(mv)
+    }
+
+    /// The line belongs to whoever completed it, whatever its marks.
+    fn outcome(&self) -> Option<Outcome> {
+        if self.winning_line().is_some() {
+            Some(Outcome::Won(opponent(self.to_move)))
+        }
+        else if self.history.len() == CELLS {
+            Some(Outcome::Draw)
+        }
+        else {
+            None
+        }
+    }
+
+    fn move_name(&self, mv: usize) -> String {
+        let (cell, mark) = move_mark(mv);
+        let mark = if mark == Field::Cross { "X" } else { "O" };
+        format!("{mark} {}", Dimensions::default().cell_name(cell))
+    }
+
+    /// A player who can complete a line wins, one who has only moves that leave a line for the
+    /// opponent loses; everything else is open.
+    fn evaluate(&self, player: Field) -> i32 {
+        let value = if !self.completing_moves().is_empty() {
+            WIN / 2
+        }
+        else if self.safe_moves().is_empty() {
+            -WIN / 2
+        }
+        else {
+            0
+        };
+        if player == self.to_move { value } else { -value }
+    }
+
+    /// A move that completes a line if there is one, otherwise the moves that leave no line to
+    /// complete. Only if there are none, all moves, which all lose.
+    fn candidate_moves(&self) -> Vec<usize> {
+        if self.outcome().is_some() {
+            return vec![];
+        }
+        if let Some(win) = self.completing_moves().first() {
+            return vec![*win];
+        }
+        let safe = self.safe_moves();
+        if safe.is_empty() { self.legal_moves() } else { safe }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_completing_player_wins() {
+        let mut game = Wild::new();
+        // the first player places two circles, the second one completes their line
+        for mv in [wild_move(0, Field::Circle), wild_move(8, Field::Cross), wild_move(1, Field::Circle)] {
+            game.play(mv).unwrap();
+        }
+        assert_eq!(vec![wild_move(2, Field::Circle)], game.candidate_moves());
+        game.play(wild_move(2, Field::Circle)).unwrap();
+        assert_eq!(Some(Outcome::Won(Field::Circle)), game.outcome());
+        assert_eq!(Some(vec![0, 1, 2]), game.winning_line());
+        assert_eq!("O c1", game.move_name(wild_move(2, Field::Circle)));
+    }
+    #[test]
+    fn test_ai_avoids_open_lines() {
+        let mut game = Wild::new();
+        game.play(wild_move(4, Field::Cross)).unwrap();
+        let mv = game.ai_move(2, Duration::from_millis(200)).unwrap();
+        // a cross anywhere lets the first player complete a line through the center
+        assert_eq!(Field::Circle, move_mark(mv).1);
+    }
+}